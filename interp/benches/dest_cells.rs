@@ -0,0 +1,66 @@
+use calyx_ir::{CellType, Direction};
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use interp::interpreter::{get_dest_cells, get_dest_cells_into};
+use interp::interpreter_ir::{Assignment, Component, ComponentBuilder, Guard};
+use std::collections::HashSet;
+use std::sync::Arc;
+
+/// A component with `cells` cells, each written by two continuous assignments
+fn component(cells: usize) -> Component {
+    let mut builder = ComponentBuilder::new("main");
+    for i in 0..cells {
+        let cell = builder.add_cell(
+            format!("c{i}").as_str(),
+            CellType::Constant { val: 0, width: 1 },
+            &[("in", 1, Direction::Input), ("out", 1, Direction::Output)],
+        );
+        let (input, output) = (cell.read().get("in"), cell.read().get("out"));
+        for _ in 0..2 {
+            builder.add_continuous_assignment(Assignment {
+                dst: input.clone(),
+                src: output.clone(),
+                guard: Arc::new(Guard::True),
+                attributes: Default::default(),
+            });
+        }
+    }
+    builder.build().unwrap()
+}
+
+/// Compares collecting the destination cells into fresh allocations on every
+/// call against reusing the same buffers
+fn dest_cells_bench(c: &mut Criterion) {
+    let mut group = c.benchmark_group("get_dest_cells");
+    for size in [8, 64, 512] {
+        let comp = component(size);
+        let assigns = &comp.continuous_assignments;
+
+        group.bench_with_input(
+            BenchmarkId::new("fresh", size),
+            &size,
+            |b, _| b.iter(|| get_dest_cells(assigns.iter(), None)),
+        );
+
+        let mut scratch = HashSet::new();
+        let mut cells = vec![];
+        group.bench_with_input(
+            BenchmarkId::new("reused", size),
+            &size,
+            |b, _| {
+                b.iter(|| {
+                    get_dest_cells_into(
+                        assigns.iter(),
+                        None,
+                        &mut scratch,
+                        &mut cells,
+                    );
+                    cells.len()
+                })
+            },
+        );
+    }
+    group.finish();
+}
+
+criterion_group!(dest_cells, dest_cells_bench);
+criterion_main!(dest_cells);
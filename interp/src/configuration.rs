@@ -1,5 +1,10 @@
-// this can be a copy type because it's just a bunch of bools
-#[derive(Debug, Default, Clone, Copy)]
+use crate::interpreter::InterpreterObserver;
+use crate::utils::StimulusMap;
+use calyx_ir::Id;
+use std::num::NonZeroU64;
+use std::sync::Arc;
+
+#[derive(Debug, Default, Clone)]
 /// Configuration struct which controls runtime behavior
 pub struct Config {
     /// enables/disables "sloppy" interpretation which returns 0 for invalid indicies
@@ -11,6 +16,21 @@ pub struct Config {
     pub allow_par_conflicts: bool,
     /// suppresses warnings
     pub quiet: bool,
+    /// the maximum number of iterations combinational convergence may take
+    /// before erroring. If `None` convergence runs until a fixed point. A
+    /// bound of zero is not representable, since every group needs at least
+    /// one iteration to settle
+    pub max_settle_iterations: Option<NonZeroU64>,
+    /// the maximum number of cycles the program may run for before the
+    /// interpreter stops early. If `None` the program runs to completion
+    pub max_cycles: Option<u64>,
+    /// the component to begin interpretation from. If `None` the program's
+    /// entrypoint (usually `main`) is used
+    pub entry_component: Option<Id>,
+    /// values to drive onto the entry component's inputs on each cycle
+    pub stimulus: Option<Arc<StimulusMap>>,
+    /// an optional observer notified of interpreter events
+    pub observer: Option<Arc<dyn InterpreterObserver>>,
 }
 
 #[derive(Default)]
@@ -19,6 +39,11 @@ pub struct ConfigBuilder {
     error_on_overflow: Option<bool>,
     allow_par_conflicts: Option<bool>,
     quiet: Option<bool>,
+    max_settle_iterations: Option<NonZeroU64>,
+    max_cycles: Option<u64>,
+    entry_component: Option<Id>,
+    stimulus: Option<Arc<StimulusMap>>,
+    observer: Option<Arc<dyn InterpreterObserver>>,
 }
 
 impl ConfigBuilder {
@@ -47,6 +72,31 @@ impl ConfigBuilder {
         self
     }
 
+    pub fn max_settle_iterations(mut self, value: Option<NonZeroU64>) -> Self {
+        self.max_settle_iterations = value;
+        self
+    }
+
+    pub fn max_cycles(mut self, value: Option<u64>) -> Self {
+        self.max_cycles = value;
+        self
+    }
+
+    pub fn entry_component(mut self, value: Option<Id>) -> Self {
+        self.entry_component = value;
+        self
+    }
+
+    pub fn stimulus(mut self, value: Arc<StimulusMap>) -> Self {
+        self.stimulus = Some(value);
+        self
+    }
+
+    pub fn observer(mut self, value: Arc<dyn InterpreterObserver>) -> Self {
+        self.observer = Some(value);
+        self
+    }
+
     pub fn build(self) -> Config {
         Config {
             allow_par_conflicts: self.allow_par_conflicts.unwrap_or_default(),
@@ -55,6 +105,11 @@ impl ConfigBuilder {
             allow_invalid_memory_access: self
                 .allow_invalid_memory_access
                 .unwrap_or_default(),
+            max_settle_iterations: self.max_settle_iterations,
+            max_cycles: self.max_cycles,
+            entry_component: self.entry_component,
+            stimulus: self.stimulus,
+            observer: self.observer,
         }
     }
 }
@@ -6,7 +6,9 @@ use super::{
 };
 use crate::environment::{InterpreterState, PrimitiveMap};
 use crate::errors::{InterpreterError, InterpreterResult};
-use crate::interpreter::{ComponentInterpreter, ConstCell, Interpreter};
+use crate::interpreter::{
+    check_supported, format_value, ComponentInterpreter, ConstCell, Interpreter,
+};
 use crate::structures::names::{CompGroupName, ComponentQualifiedInstanceName};
 use crate::structures::state_views::StateView;
 use crate::utils::AsRaw;
@@ -50,6 +52,9 @@ impl Debugger {
         &mut self,
         env: InterpreterState,
     ) -> InterpreterResult<InterpreterState> {
+        for component in self._context.iter() {
+            check_supported(&component.control)?;
+        }
         let qin = ComponentQualifiedInstanceName::new_single(
             &self.main_component,
             self.main_component.name,
@@ -524,20 +529,18 @@ fn print_cell(
                     SPACING,
                     port.read().name.red(),
                     if let Some(code) = code {
+                        let formatted = format_value(&v, *code);
                         match code {
-                            PrintCode::Unsigned => {
-                                format!("{}", v.as_unsigned())
-                            }
-                            PrintCode::Signed => {
-                                format!("{}", v.as_signed().green())
+                            PrintCode::Unsigned => formatted,
+                            PrintCode::Signed => formatted.green().to_string(),
+                            PrintCode::Hex => formatted.yellow().to_string(),
+                            PrintCode::UFixed(_) => {
+                                formatted.blue().to_string()
                             }
-                            PrintCode::UFixed(num) => {
-                                format!("{}", v.as_ufp(*num).blue())
+                            PrintCode::SFixed(_) => {
+                                formatted.purple().to_string()
                             }
-                            PrintCode::SFixed(num) => {
-                                format!("{}", v.as_sfp(*num).purple())
-                            }
-                            PrintCode::Binary => format!("{}", v.cyan()),
+                            PrintCode::Binary => formatted.cyan().to_string(),
                         }
                     } else {
                         format!("{}", &v.magenta())
@@ -571,13 +574,7 @@ fn print_port(
         SPACING,
         parent_name.red(),
         port_ref.name.green(),
-        match code {
-            PrintCode::Unsigned => format!("{}", v.as_unsigned()),
-            PrintCode::Signed => format!("{}", v.as_signed()),
-            PrintCode::UFixed(num) => format!("{}", v.as_ufp(num)),
-            PrintCode::SFixed(num) => format!("{}", v.as_sfp(num)),
-            PrintCode::Binary => format!("{}", v),
-        }
+        format_value(&v, code)
     )
 }
 
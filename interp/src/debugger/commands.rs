@@ -5,8 +5,10 @@ use owo_colors::OwoColorize;
 use std::{
     fmt::{Display, Write},
     marker::PhantomData,
+    str::FromStr,
 };
 
+use crate::errors::{BoxedInterpreterError, InterpreterError};
 use crate::structures::names::CompGroupName;
 
 #[derive(Debug)]
@@ -69,11 +71,12 @@ impl From<u64> for BreakPointId {
     }
 }
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum PrintCode {
     Binary,
     Unsigned,
     Signed,
+    Hex,
     UFixed(usize),
     SFixed(usize),
 }
@@ -93,6 +96,7 @@ impl Display for PrintCode {
                 PrintCode::Binary => "\\b".cyan().to_string(),
                 PrintCode::Unsigned => "\\u".blue().to_string(),
                 PrintCode::Signed => "\\s".yellow().to_string(),
+                PrintCode::Hex => "\\x".purple().to_string(),
                 PrintCode::UFixed(n) => format!("\\u.{}", n),
                 PrintCode::SFixed(n) => format!("\\s.{}", n),
             }
@@ -100,6 +104,60 @@ impl Display for PrintCode {
     }
 }
 
+/// Parses the spellings used on the command line, ignoring case: `u` or
+/// `unsigned`, `s` or `signed`, `b` or `binary`, `x` or `hex`, and `u.N` or
+/// `s.N` for fixed point values with `N` fractional bits. A leading `\` as in
+/// the debugger, and the colors added by [Display], are also accepted, so
+/// every code round-trips through its string form.
+impl FromStr for PrintCode {
+    type Err = BoxedInterpreterError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let plain = strip_colors(s).to_lowercase();
+        let code = plain.strip_prefix('\\').unwrap_or(&plain);
+        let fixed = |frac: &str| frac.parse::<usize>().ok();
+
+        let parsed = match code {
+            "u" | "unsigned" => Some(PrintCode::Unsigned),
+            "s" | "signed" => Some(PrintCode::Signed),
+            "b" | "binary" => Some(PrintCode::Binary),
+            "x" | "hex" => Some(PrintCode::Hex),
+            _ => {
+                if let Some(frac) = code.strip_prefix("u.") {
+                    fixed(frac).map(PrintCode::UFixed)
+                } else if let Some(frac) = code.strip_prefix("s.") {
+                    fixed(frac).map(PrintCode::SFixed)
+                } else {
+                    None
+                }
+            }
+        };
+
+        parsed.ok_or_else(|| {
+            InterpreterError::UnknownPrintCode(s.to_string()).into()
+        })
+    }
+}
+
+/// Removes ANSI color sequences, such as those written by [OwoColorize].
+fn strip_colors(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    let mut chars = s.chars();
+    while let Some(c) = chars.next() {
+        if c == '\x1b' {
+            // sequences run from the escape to the first letter
+            for c in chars.by_ref() {
+                if c.is_ascii_alphabetic() {
+                    break;
+                }
+            }
+        } else {
+            out.push(c);
+        }
+    }
+    out
+}
+
 #[derive(Clone, Copy, Debug)]
 pub enum WatchPosition {
     Before,
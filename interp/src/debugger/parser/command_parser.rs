@@ -72,6 +72,10 @@ impl CommandParser {
         Ok(())
     }
 
+    fn pc_hex(_input: Node) -> ParseResult<()> {
+        Ok(())
+    }
+
     fn before(_input: Node) -> ParseResult<()> {
         Ok(())
     }
@@ -107,6 +111,7 @@ impl CommandParser {
         match_nodes!(input.into_children();
             [pc_s(_)] => Ok(PrintCode::Signed),
             [pc_un(_)] => Ok(PrintCode::Unsigned),
+            [pc_hex(_)] => Ok(PrintCode::Hex),
             [pc_ufx(n)] => Ok(PrintCode::UFixed(n)),
             [pc_sfx(n)] => Ok(PrintCode::SFixed(n)),
         )
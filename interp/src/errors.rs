@@ -1,4 +1,5 @@
 use crate::interpreter_ir;
+use crate::structures::state_views::FullySerialize;
 use crate::values::Value;
 use calyx_ir::{self as ir, Id};
 use calyx_utils::Error as CalyxError;
@@ -55,6 +56,10 @@ pub enum InterpreterError {
     #[error("unknown command - {0}")]
     UnknownCommand(String),
 
+    /// The given print code is not one the interpreter knows
+    #[error("unknown print code \"{0}\". Expected one of u, unsigned, s, signed, b, binary, x, hex, u.N, or s.N")]
+    UnknownPrintCode(String),
+
     /// Unable to parse the debugger command
     #[error(transparent)]
     ParseError(
@@ -121,6 +126,8 @@ pub enum InterpreterError {
     InvalidIfState,
     #[error("invalid internal while state. This should never happen, please report it")]
     InvalidWhileState,
+    #[error("invalid internal repeat state. This should never happen, please report it")]
+    InvalidRepeatState,
 
     #[error("{mem_dim} Memory given initialization data with invalid dimension.
     When flattened, expected {expected} entries, but the memory was supplied with {given} entries instead.
@@ -131,6 +138,51 @@ pub enum InterpreterError {
         given: usize,
     },
 
+    #[error("memory \"{cell}\" has {expected} entries but the data file supplies {given}")]
+    MemoryInitLength {
+        cell: Id,
+        expected: u64,
+        given: usize,
+    },
+
+    #[error("entry {index} supplied for memory \"{cell}\" does not fit in the memory width of {width}")]
+    MemoryInitOverflow { cell: Id, index: usize, width: u64 },
+
+    #[error("memory \"{memory}\" cannot be written with a single width: entry {index} is {given} bits wide but entry 0 is {expected}")]
+    MixedMemoryWidths {
+        memory: Id,
+        index: usize,
+        expected: u64,
+        given: u64,
+    },
+
+    #[error("group \"{0}\" was created but never completed by the translator")]
+    PartiallyTranslatedGroup(Id),
+
+    #[error("invoke of component \"{component}\" passes a cell for \"{cell}\" which is not one of its ref cells")]
+    NotAReferenceCell { component: Id, cell: Id },
+
+    #[error("invoke of cell \"{cell}\" binds \"{port}\" which is not an {expected} port of the cell")]
+    InvalidInvokePort {
+        cell: Id,
+        port: Id,
+        expected: &'static str,
+    },
+
+    #[error(
+        "control enables group \"{0}\" which is not defined in the component"
+    )]
+    UndefinedGroupEnable(Id),
+
+    #[error("{kind} \"{name}\" has no counterpart in the Calyx component being built")]
+    UnresolvedIrReference { kind: &'static str, name: String },
+
+    #[error("group \"{group}\" has no {hole} hole. Expected a hole with the @{hole} attribute or one named \"{hole}\"")]
+    MissingGroupHole { group: Id, hole: String },
+
+    #[error("parameter \"{0}\" is bound more than once")]
+    DuplicateParameter(Id),
+
     #[error("interpreter does not have an implementation of the \"{0}\" primitive. If the interpreter should have an implementation of this primitive please open a github issue or PR.")]
     UnknownPrimitive(String),
     #[error("program evaluated the truth value of a wire \"{}.{}\" which is not one bit. Wire is {} bits wide.", 0.0, 0.1, 1)]
@@ -149,27 +201,97 @@ pub enum InterpreterError {
     #[error("Both read and write signals provided to the sequential memory.")]
     SeqMemoryError,
 
+    #[error("bit pattern has bit {high_bit} set which does not fit in a value of width {width}")]
+    BitPatternTooWide { width: u64, high_bit: usize },
+
+    #[error(
+        "cannot {operation} a value of width {width} to width {new_width}"
+    )]
+    InvalidResize {
+        operation: &'static str,
+        width: u64,
+        new_width: u64,
+    },
+
+    #[error("bitwise operation applied to values of width {left} and {right}, widths must match")]
+    BitwiseWidthMismatch { left: u64, right: u64 },
+
+    #[error("cannot concatenate values of width {high} and {low}, the combined width is too large to represent")]
+    ConcatTooWide { high: u64, low: u64 },
+
+    #[error(
+        "bit range [{start}, {end}) is not valid for a value of width {width}"
+    )]
+    InvalidBitRange {
+        start: usize,
+        end: usize,
+        width: u64,
+    },
+
+    #[error("stimulus given for \"{0}\" which is not an input port of the entry component")]
+    UnknownStimulusPort(String),
+
+    #[error("stimulus for \"{port}\" has width {given} but the port has width {expected}")]
+    StimulusWidthMismatch {
+        port: String,
+        expected: u64,
+        given: u64,
+    },
+
+    /// The program uses a construct which the interpreter cannot run
+    #[error("the interpreter does not support {construct}. Please run the compiler passes which remove it")]
+    UnsupportedConstruct { construct: &'static str },
+
+    #[error("combinational cycle found: {}", .0.join(" -> "))]
+    CombinationalCycle(Vec<String>),
+
+    #[error("combinational convergence did not settle after {iterations} iterations. Ports still changing on the final iteration: {}", ports.join(", "))]
+    SettleDidNotConverge { iterations: u64, ports: Vec<String> },
+
+    /// The program did not finish within the configured cycle budget. Carries
+    /// the state of the program at the point it was stopped
+    #[error("program did not finish within the budget of {cycles} cycles")]
+    CycleBudgetExceeded {
+        cycles: u64,
+        state: Box<FullySerialize>,
+    },
+
     // TODO (Griffin): Make this error message better please
     #[error("Computation has under/overflowed its bounds")]
     OverflowError,
 
+    #[error("unable to parse memory file \"{}\": {msg}", path.display())]
+    InvalidMemoryFile {
+        path: std::path::PathBuf,
+        msg: String,
+    },
+
+    #[error("unable to parse memory data: {0}")]
+    InvalidMemoryData(String),
+
+    #[error("binary memory file \"{}\" is {actual} bytes but the layout requires {expected}", path.display())]
+    BinaryMemoryLength {
+        path: std::path::PathBuf,
+        expected: usize,
+        actual: usize,
+    },
+
     #[error(transparent)]
     IOError(#[from] std::io::Error),
 }
 
 impl InterpreterError {
     pub fn conflicting_assignments(
-        _port_id: Id,
-        _parent_id: Id,
-        _a1: &interpreter_ir::Assignment<ir::Nothing>,
-        _a2: &interpreter_ir::Assignment<ir::Nothing>,
+        port_id: Id,
+        parent_id: Id,
+        a1: &interpreter_ir::Assignment<ir::Nothing>,
+        a2: &interpreter_ir::Assignment<ir::Nothing>,
     ) -> Self {
-        #[allow(unreachable_code)]
         Self::ConflictingAssignments {
-            port_id: _port_id,
-            parent_id: _parent_id,
-            a1: todo!(),
-            a2: todo!(),
+            port_id,
+            parent_id,
+            a1: a1.to_string(),
+            a2: a2.to_string(),
         }
     }
 }
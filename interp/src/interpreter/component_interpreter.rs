@@ -2,15 +2,17 @@ use std::{collections::HashSet, sync::Arc};
 
 use super::{
     control_interpreter::{
-        ComponentInfo, ControlInterpreter, StructuralInterpreter,
+        check_supported, ComponentInfo, ControlInterpreter,
+        StructuralInterpreter,
     },
+    program_interpreter::{ProgramInterpreter, StepResult},
     utils::control_is_empty,
     Interpreter,
 };
 use crate::{
     debugger::{name_tree::ActiveTreeNode, PrintCode},
     environment::InterpreterState,
-    errors::InterpreterResult,
+    errors::{InterpreterError, InterpreterResult},
     interpreter_ir::{self as iir, Component, Control, Port, PortParent},
     primitives::{Named, Primitive},
     structures::names::{
@@ -208,17 +210,34 @@ impl ComponentInterpreter {
         self.get_env_mut().insert(raw, Value::bit_low())
     }
 
-    /// Interpret a calyx program from the root
+    /// Interpret a calyx program from the root. Fails with
+    /// [InterpreterError::UnsupportedConstruct] before running anything if
+    /// any component uses a construct the interpreter cannot run.
     pub fn interpret_program(
         env: InterpreterState,
         comp: &Arc<iir::Component>,
     ) -> InterpreterResult<InterpreterState> {
-        let qin = ComponentQualifiedInstanceName::new_single(comp, comp.name);
-        let mut main_comp = Self::from_component(comp, env, qin);
-        main_comp.set_go_high();
-        main_comp.run()?;
-        main_comp.set_go_low();
-        main_comp.deconstruct()
+        for component in env.context.iter() {
+            check_supported(&component.control)?;
+        }
+        let max_cycles = env.max_cycles();
+        if let Some(stimulus) = env.stimulus() {
+            stimulus.validate(comp)?;
+        }
+        let mut program = ProgramInterpreter::new(env, comp);
+        match max_cycles {
+            Some(budget) => {
+                if program.step_n(budget)? == StepResult::Running {
+                    return Err(InterpreterError::CycleBudgetExceeded {
+                        cycles: program.cycles(),
+                        state: Box::new(program.state().gen_serializer(false)),
+                    }
+                    .into());
+                }
+            }
+            None => program.run()?,
+        }
+        program.finish()
     }
 }
 
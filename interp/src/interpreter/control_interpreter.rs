@@ -1,7 +1,7 @@
-use super::group_interpreter::{finish_interpretation, AssignmentInterpreter};
+use super::group_interpreter::AssignmentInterpreter;
 use super::utils::{get_done_port, get_go_port};
 use super::Interpreter;
-use crate::errors::InterpreterError;
+use crate::errors::{BoxedInterpreterError, InterpreterError};
 use crate::structures::names::{
     ComponentQualifiedInstanceName, GroupQIN, GroupQualifiedInstanceName,
 };
@@ -30,6 +30,42 @@ use std::{collections::HashSet, sync::Arc};
 const POS_TAG: orig_ir::Attribute =
     orig_ir::Attribute::Num(orig_ir::NumAttr::Pos);
 
+const STATIC_CONTROL: &str = "static control";
+const REF_CELLS: &str = "invokes which pass ref cells";
+
+fn unsupported(construct: &'static str) -> InterpreterError {
+    InterpreterError::UnsupportedConstruct { construct }
+}
+
+/// Checks that `control` contains nothing the interpreter cannot run, that
+/// is no static control and no invokes which pass ref cells. The
+/// constructors of the control interpreters panic on reaching either, so
+/// entry points should call this on every component first.
+pub fn check_supported(control: &Control) -> InterpreterResult<()> {
+    let mut checker = SupportChecker { result: Ok(()) };
+    checker.walk(control);
+    checker.result
+}
+
+/// Records the first unsupported construct visited. See [check_supported].
+struct SupportChecker {
+    result: InterpreterResult<()>,
+}
+
+impl ControlVisitor for SupportChecker {
+    fn visit_invoke(&mut self, invoke: &Invoke) {
+        if self.result.is_ok() && !invoke.ref_cells.is_empty() {
+            self.result = Err(unsupported(REF_CELLS).into());
+        }
+    }
+
+    fn visit_static(&mut self, _control: &StaticControl) {
+        if self.result.is_ok() {
+            self.result = Err(unsupported(STATIC_CONTROL).into());
+        }
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct ComponentInfo {
     pub continuous_assignments: ContinuousAssignments,
@@ -108,19 +144,19 @@ pub enum EnableHolder {
 }
 
 impl EnableHolder {
-    fn done_port(&self) -> Option<ArcTex<Port>> {
+    fn done_port(&self) -> InterpreterResult<Option<ArcTex<Port>>> {
         match self {
-            EnableHolder::Group(g) => Some(get_done_port(&g.read())),
-            EnableHolder::CombGroup(_) | EnableHolder::Vec(_) => None,
-            EnableHolder::Enable(e) => Some(get_done_port(&e.group.read())),
+            EnableHolder::Group(g) => get_done_port(&g.read()).map(Some),
+            EnableHolder::CombGroup(_) | EnableHolder::Vec(_) => Ok(None),
+            EnableHolder::Enable(e) => get_done_port(&e.group.read()).map(Some),
         }
     }
 
-    fn go_port(&self) -> Option<ArcTex<Port>> {
+    fn go_port(&self) -> InterpreterResult<Option<ArcTex<Port>>> {
         match self {
-            EnableHolder::Group(g) => Some(get_go_port(&g.read())),
-            EnableHolder::CombGroup(_) | EnableHolder::Vec(_) => None,
-            EnableHolder::Enable(e) => Some(get_go_port(&e.group.read())),
+            EnableHolder::Group(g) => get_go_port(&g.read()).map(Some),
+            EnableHolder::CombGroup(_) | EnableHolder::Vec(_) => Ok(None),
+            EnableHolder::Enable(e) => get_go_port(&e.group.read()).map(Some),
         }
     }
 
@@ -179,8 +215,12 @@ impl From<Arc<Enable>> for EnableHolder {
 pub struct EnableInterpreter {
     enable: EnableHolder,
     group_name: Option<Id>,
+    go: Option<ArcTex<Port>>,
     interp: AssignmentInterpreter,
     qin: ComponentQualifiedInstanceName,
+    /// An error from resolving the group's interface holes. Construction
+    /// cannot fail, so this is reported by the first call that can.
+    error: Option<BoxedInterpreterError>,
 }
 
 impl EnableInterpreter {
@@ -196,30 +236,61 @@ impl EnableInterpreter {
     {
         let enable: EnableHolder = enable.into();
 
-        if let Some(go) = enable.go_port() {
+        if let Some(observer) = env.observer() {
+            match &enable {
+                EnableHolder::Group(g) => {
+                    observer.on_group_activated(&g.read())
+                }
+                EnableHolder::Enable(e) => {
+                    observer.on_group_activated(&e.group.read())
+                }
+                EnableHolder::CombGroup(_) | EnableHolder::Vec(_) => {}
+            }
+        }
+
+        let holes = enable
+            .go_port()
+            .and_then(|go| Ok((go, enable.done_port()?)));
+        let (go, done, error) = match holes {
+            Ok((go, done)) => (go, done, None),
+            Err(e) => (None, None, Some(e)),
+        };
+
+        if let Some(go) = &go {
             env.insert(go, Value::bit_high())
         }
 
         let assigns = enable.clone();
-        let done = enable.done_port();
         let interp = AssignmentInterpreter::new(env, done, assigns, continuous);
         Self {
             enable,
             group_name,
+            go,
             interp,
             qin: qin.clone(),
+            error,
         }
     }
 }
 
 impl EnableInterpreter {
     fn reset(mut self) -> InterpreterResult<InterpreterState> {
-        if let Some(go) = self.enable.go_port() {
+        self.check_holes()?;
+        if let Some(go) = &self.go {
             self.interp.get_mut_env().insert(go, Value::bit_low())
         }
 
         self.interp.reset()
     }
+
+    /// Reports the error from resolving the interface holes, if there was one
+    fn check_holes(&mut self) -> InterpreterResult<()> {
+        match self.error.take() {
+            Some(e) => Err(e),
+            None => Ok(()),
+        }
+    }
+
     fn get(&self, port: impl AsRaw<Port>) -> &Value {
         self.interp.get(port)
     }
@@ -227,10 +298,12 @@ impl EnableInterpreter {
 
 impl Interpreter for EnableInterpreter {
     fn step(&mut self) -> InterpreterResult<()> {
+        self.check_holes()?;
         self.interp.step()
     }
 
     fn run(&mut self) -> InterpreterResult<()> {
+        self.check_holes()?;
         self.interp.run()
     }
 
@@ -259,6 +332,7 @@ impl Interpreter for EnableInterpreter {
     }
 
     fn converge(&mut self) -> InterpreterResult<()> {
+        self.check_holes()?;
         self.interp.step_convergence()
     }
 
@@ -572,19 +646,20 @@ impl IfInterpreter {
         env: InterpreterState,
         info: ComponentInfo,
     ) -> Self {
-        let state = if let Some(grp) = &ctrl_if.cond {
-            let grp_ref = grp.read();
-            let name = Some(grp_ref.name());
-            let enable = EnableInterpreter::new(
-                grp,
-                name,
-                env,
-                info.continuous_assignments.clone(),
-                &info.qin,
-            );
-            IfFsm::ConditionWith(enable.into())
-        } else {
-            IfFsm::ConditionPort(env)
+        let state = match ctrl_if.condition_kind() {
+            ConditionKind::CombGroup(grp) => {
+                let grp_ref = grp.read();
+                let name = Some(grp_ref.name());
+                let enable = EnableInterpreter::new(
+                    grp,
+                    name,
+                    env,
+                    info.continuous_assignments.clone(),
+                    &info.qin,
+                );
+                IfFsm::ConditionWith(enable.into())
+            }
+            ConditionKind::Port(_) => IfFsm::ConditionPort(env),
         };
 
         Self {
@@ -807,19 +882,20 @@ impl WhileInterpreter {
 
     /// Utility method whichs handles a return to the appropriate condition state
     fn process_initial_state(&mut self, env: InterpreterState) {
-        if let Some(cond_grp) = &self.wh.cond {
-            let grp_ref = cond_grp.read();
-            let name = grp_ref.name();
-            let interp = EnableInterpreter::new(
-                cond_grp.clone(),
-                Some(name),
-                env,
-                self.info.continuous_assignments.clone(),
-                &self.info.qin,
-            );
-            self.state = WhileFsm::CondWith(interp.into());
-        } else {
-            self.state = WhileFsm::CondPort(env);
+        match self.wh.condition_kind() {
+            ConditionKind::CombGroup(cond_grp) => {
+                let grp_ref = cond_grp.read();
+                let name = grp_ref.name();
+                let interp = EnableInterpreter::new(
+                    cond_grp.clone(),
+                    Some(name),
+                    env,
+                    self.info.continuous_assignments.clone(),
+                    &self.info.qin,
+                );
+                self.state = WhileFsm::CondWith(interp.into());
+            }
+            ConditionKind::Port(_) => self.state = WhileFsm::CondPort(env),
         }
     }
 
@@ -1002,6 +1078,178 @@ impl Interpreter for WhileInterpreter {
         }
     }
 }
+
+enum RepeatFsm {
+    Err, // transient error state
+    Body(ControlInterpreter),
+    Done(InterpreterState),
+}
+
+impl Default for RepeatFsm {
+    fn default() -> Self {
+        Self::Err
+    }
+}
+
+/// Runs the body of a repeat `num_repeats` times. The body is held once and
+/// a fresh interpreter is built for it on each iteration.
+pub struct RepeatInterpreter {
+    state: RepeatFsm,
+    repeat: Arc<Repeat>,
+    info: ComponentInfo,
+    /// The number of iterations which have been started
+    iteration: u64,
+}
+
+impl RepeatInterpreter {
+    pub fn new(
+        repeat: Arc<Repeat>,
+        env: InterpreterState,
+        info: ComponentInfo,
+    ) -> Self {
+        let mut out = Self {
+            state: RepeatFsm::Err,
+            repeat,
+            info,
+            iteration: 0,
+        };
+        out.next_iteration(env);
+        out
+    }
+
+    /// Starts the next iteration of the body, or moves to done if every
+    /// iteration has been run
+    fn next_iteration(&mut self, env: InterpreterState) {
+        if self.iteration < self.repeat.num_repeats {
+            self.iteration += 1;
+            let interp = ControlInterpreter::new(
+                self.repeat.body.clone(),
+                env,
+                &self.info,
+            );
+            self.state = RepeatFsm::Body(interp);
+        } else {
+            self.state = RepeatFsm::Done(env);
+        }
+    }
+}
+
+impl Interpreter for RepeatInterpreter {
+    fn step(&mut self) -> InterpreterResult<()> {
+        match &mut self.state {
+            RepeatFsm::Err => Err(InterpreterError::InvalidRepeatState.into()),
+            RepeatFsm::Body(b) => {
+                if b.is_done() {
+                    if let RepeatFsm::Body(b) = std::mem::take(&mut self.state)
+                    {
+                        let env = b.deconstruct()?;
+                        self.next_iteration(env);
+                    } else {
+                        unreachable!()
+                    }
+                } else {
+                    b.step()?;
+                }
+                Ok(())
+            }
+            RepeatFsm::Done(_) => Ok(()),
+        }
+    }
+
+    fn run(&mut self) -> InterpreterResult<()> {
+        match &mut self.state {
+            RepeatFsm::Err => Err(InterpreterError::InvalidRepeatState.into()),
+            RepeatFsm::Body(_) => {
+                if let RepeatFsm::Body(b) = std::mem::take(&mut self.state) {
+                    let mut env = b.run_and_deconstruct()?;
+                    while self.iteration < self.repeat.num_repeats {
+                        self.iteration += 1;
+                        env = ControlInterpreter::new(
+                            self.repeat.body.clone(),
+                            env,
+                            &self.info,
+                        )
+                        .run_and_deconstruct()?;
+                    }
+                    self.state = RepeatFsm::Done(env);
+                    Ok(())
+                } else {
+                    unreachable!()
+                }
+            }
+            RepeatFsm::Done(_) => Ok(()),
+        }
+    }
+
+    fn deconstruct(self) -> InterpreterResult<InterpreterState> {
+        match self.state {
+            RepeatFsm::Done(e) => Ok(e),
+            _ => Err(InterpreterError::InvalidRepeatState.into()),
+        }
+    }
+
+    fn is_done(&self) -> bool {
+        matches!(self.state, RepeatFsm::Done(_))
+    }
+
+    fn get_env(&self) -> StateView<'_> {
+        match &self.state {
+            RepeatFsm::Err => unreachable!("There is an error in the Repeat state transition. Please report this."),
+            RepeatFsm::Done(e) => e.into(),
+            RepeatFsm::Body(b) => b.get_env(),
+        }
+    }
+
+    fn currently_executing_group(&self) -> HashSet<GroupQIN> {
+        match &self.state {
+            RepeatFsm::Err => unreachable!("There is an error in the Repeat state transition. Please report this."),
+            RepeatFsm::Done(_) => HashSet::new(),
+            RepeatFsm::Body(b) => b.currently_executing_group(),
+        }
+    }
+
+    fn get_env_mut(&mut self) -> MutStateView<'_> {
+        match &mut self.state {
+            RepeatFsm::Err => unreachable!("There is an error in the Repeat state transition. Please report this."),
+            RepeatFsm::Done(e) => e.into(),
+            RepeatFsm::Body(b) => b.get_env_mut(),
+        }
+    }
+
+    fn converge(&mut self) -> InterpreterResult<()> {
+        match &mut self.state {
+            RepeatFsm::Err => Err(InterpreterError::InvalidRepeatState.into()),
+            RepeatFsm::Body(b) => b.converge(),
+            RepeatFsm::Done(_) => {
+                if let RepeatFsm::Done(env) = std::mem::take(&mut self.state) {
+                    let mut interp = EnableInterpreter::new(
+                        vec![],
+                        None,
+                        env,
+                        self.info.continuous_assignments.clone(),
+                        &self.info.qin,
+                    );
+                    interp.converge()?;
+                    let env = interp.deconstruct()?;
+
+                    self.state = RepeatFsm::Done(env);
+                    Ok(())
+                } else {
+                    unreachable!()
+                }
+            }
+        }
+    }
+
+    fn get_active_tree(&self) -> Vec<ActiveTreeNode> {
+        match &self.state {
+            RepeatFsm::Err => unreachable!("There is an error in the Repeat state transition. Please report this."),
+            RepeatFsm::Done(_) => vec![],
+            RepeatFsm::Body(b) => b.get_active_tree(),
+        }
+    }
+}
+
 pub struct InvokeInterpreter {
     invoke: Arc<Invoke>,
     assign_interp: AssignmentInterpreter,
@@ -1020,7 +1268,7 @@ impl InvokeInterpreter {
         let comp_cell = invoke.comp.read();
 
         if !invoke.ref_cells.is_empty() {
-            todo!("Interpreter does not currently support ref-cells. Please run the compile-ref pass.")
+            panic!("{}", unsupported(REF_CELLS));
         }
 
         //first connect the inputs (from connection -> input)
@@ -1148,6 +1396,7 @@ macro_rules! control_match {
             ControlInterpreter::Par($name) => $exp,
             ControlInterpreter::If($name) => $exp,
             ControlInterpreter::While($name) => $exp,
+            ControlInterpreter::Repeat($name) => $exp,
             ControlInterpreter::Invoke($name) => $exp,
         }
     }};
@@ -1160,6 +1409,7 @@ pub enum ControlInterpreter {
     Par(Box<ParInterpreter>),
     If(Box<IfInterpreter>),
     While(Box<WhileInterpreter>),
+    Repeat(Box<RepeatInterpreter>),
     Invoke(Box<InvokeInterpreter>),
 }
 
@@ -1205,8 +1455,24 @@ impl ControlInterpreter {
             Control::Empty(_) => {
                 Self::Empty(Box::new(EmptyInterpreter::new(env)))
             }
+            Control::Repeat(r) => Self::Repeat(Box::new(
+                RepeatInterpreter::new(r, env, info.clone()),
+            )),
+            Control::Static(_) => panic!("{}", unsupported(STATIC_CONTROL)),
         }
     }
+
+    /// As [ControlInterpreter::new], but first checks the whole of `control`
+    /// with [check_supported] rather than panicking once an unsupported
+    /// construct is reached.
+    pub fn try_new(
+        control: Control,
+        env: InterpreterState,
+        info: &ComponentInfo,
+    ) -> InterpreterResult<Self> {
+        check_supported(&control)?;
+        Ok(Self::new(control, env, info))
+    }
 }
 
 impl Interpreter for ControlInterpreter {
@@ -1287,12 +1553,8 @@ impl Interpreter for StructuralInterpreter {
     }
 
     fn deconstruct(self) -> InterpreterResult<InterpreterState> {
-        let final_env = self.interp.deconstruct()?;
-        finish_interpretation(
-            final_env,
-            Some(self.done_port),
-            self.continuous.iter(),
-        )
+        self.interp
+            .finish(Some(self.done_port), self.continuous.iter())
     }
 
     fn run(&mut self) -> InterpreterResult<()> {
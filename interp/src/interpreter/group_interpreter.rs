@@ -3,7 +3,10 @@ use crate::{environment::InterpreterState, utils::ArcTex};
 use crate::{
     errors::{InterpreterError, InterpreterResult},
 };
-use crate::{interpreter::utils::get_dest_cells, interpreter_ir::Assignment};
+use crate::{
+    interpreter::utils::get_dest_cells_into,
+    interpreter_ir::Assignment,
+};
 use crate::{
     interpreter_ir::{Cell, *},
     utils::{AsRaw},
@@ -11,6 +14,7 @@ use crate::{
 use crate::{utils::ArcTexOrConst, values::Value};
 use calyx_ir::{self as ir};
 use ir::Nothing;
+use itertools::Itertools;
 use parking_lot::RwLockReadGuard;
 use std::collections::{HashMap, HashSet};
 
@@ -111,6 +115,10 @@ pub struct AssignmentInterpreter {
     val_changed: Option<bool>,
     possible_ports: HashSet<*const Port>,
     port_lookup_map: HashMap<*const Port, ArcTex<Port>>,
+    /// Buffers for the destination cells found in [Self::step_convergence],
+    /// kept so they are not reallocated on every call
+    dest_scratch: HashSet<*const Cell>,
+    dest_cells: Vec<ArcTex<Cell>>,
 }
 
 impl AssignmentInterpreter {
@@ -148,6 +156,8 @@ impl AssignmentInterpreter {
             val_changed: None,
             possible_ports,
             port_lookup_map,
+            dest_scratch: HashSet::new(),
+            dest_cells: vec![],
         }
     }
 
@@ -185,11 +195,20 @@ impl AssignmentInterpreter {
                             )
                         });
 
+                    if let Some(observer) = self.state.observer() {
+                        observer.on_cell_written(&cell.read(), port, &val);
+                    }
                     update_list.push((Arc::clone(&port_ref), val));
                 }
             }
         }
 
+        if let (Some(observer), AssignmentHolder::Group(grp)) =
+            (self.state.observer(), &self.assigns)
+        {
+            observer.on_group_cycle(&grp.read());
+        }
+
         let state_map = &mut self.state;
 
         for (port, val) in update_list {
@@ -225,8 +244,35 @@ impl AssignmentInterpreter {
         //         .map(|x| x.dst.as_raw())
         //         .any(|x| x == self.done_port.unwrap()))
         // }
+        let max_iterations = self.state.max_settle_iterations();
+        let mut iterations: u64 = 0;
+        // ports which changed on the previous iteration. Only tracked when the
+        // number of iterations is bounded
+        let mut changed_ports: Vec<ArcTex<Port>> = vec![];
+
         // this unwrap is safe
         while self.val_changed.unwrap() {
+            if let Some(max) = max_iterations {
+                if iterations >= max.get() {
+                    // a port may have changed more than once on the final
+                    // iteration, e.g. as a destination and as a primitive
+                    // output
+                    let mut seen: HashSet<*const Port> = HashSet::new();
+                    return Err(InterpreterError::SettleDidNotConverge {
+                        iterations,
+                        ports: changed_ports
+                            .iter()
+                            .filter(|&p| seen.insert(p.as_raw()))
+                            .map(|p| p.read().canonical().to_string())
+                            .sorted()
+                            .collect(),
+                    }
+                    .into());
+                }
+                changed_ports.clear();
+            }
+            iterations += 1;
+
             self.val_changed = Some(false);
 
             // for change based simulation
@@ -246,12 +292,15 @@ impl AssignmentInterpreter {
                                 .eval_guard(&assignment.guard)
                                 .unwrap()
                             {
-                                Some((
-                                    assignment.dst.clone(),
-                                    self.state
-                                        .get_from_port(&assignment.src)
-                                        .clone(),
-                                ))
+                                let val = self
+                                    .state
+                                    .get_from_port(&assignment.src)
+                                    .clone();
+                                if let Some(observer) = self.state.observer() {
+                                    observer
+                                        .on_assignment_fired(assignment, &val);
+                                }
+                                Some((assignment.dst.clone(), val))
                             } else {
                                 None
                             }
@@ -285,7 +334,11 @@ impl AssignmentInterpreter {
                 let new_val = Value::from(0, old_val_width);
 
                 if old_val.as_unsigned() != 0_u32.into() {
-                    let port_ref = &self.port_lookup_map[&port].read();
+                    let port_arc = &self.port_lookup_map[&port];
+                    if max_iterations.is_some() {
+                        changed_ports.push(Arc::clone(port_arc));
+                    }
+                    let port_ref = port_arc.read();
                     if let PortParent::Cell(cell) = &port_ref.parent {
                         let cell_rrc = cell.upgrade();
                         if cells_to_run_set.insert(cell_rrc.as_raw()) {
@@ -304,6 +357,9 @@ impl AssignmentInterpreter {
             for (port, value) in updates_list.drain(..) {
                 if self.state.get_from_port(&port) != &value {
                     self.state.insert(port.clone(), value);
+                    if max_iterations.is_some() {
+                        changed_ports.push(port.clone());
+                    }
 
                     if let PortParent::Cell(c) = &port.read().parent {
                         let c = c.upgrade();
@@ -317,7 +373,7 @@ impl AssignmentInterpreter {
 
             // dbg!(&cells_to_run_rrc);
 
-            let changed = eval_prims(
+            let changed = eval_prims_tracked(
                 &mut self.state,
                 if first_iteration {
                     self.cells.iter()
@@ -326,8 +382,11 @@ impl AssignmentInterpreter {
                 },
                 false,
             )?;
-            if changed {
+            if !changed.is_empty() {
                 self.val_changed = Some(true);
+                if max_iterations.is_some() {
+                    changed_ports.extend(changed);
+                }
             }
 
             first_iteration = false;
@@ -364,10 +423,14 @@ impl AssignmentInterpreter {
 
             // TODO Griffin: check if none here is actually okay. I think it is,
             // but it merits further thinking
-            let cells =
-                utils::get_dest_cells(self.assigns.get_ref().iter(), None);
-
-            eval_prims(&mut self.state, cells.iter(), false)?;
+            get_dest_cells_into(
+                self.assigns.get_ref().iter(),
+                None,
+                &mut self.dest_scratch,
+                &mut self.dest_cells,
+            );
+
+            eval_prims(&mut self.state, self.dest_cells.iter(), false)?;
             self.converge_assignments(ConvergeType::Continuous)?;
         }
 
@@ -430,12 +493,35 @@ impl AssignmentInterpreter {
     pub fn reset(mut self) -> InterpreterResult<InterpreterState> {
         let assigns = std::mem::take(&mut self.assigns);
         let done_signal = self.done_port;
-        let env = self.deconstruct()?;
-
         let assign_ref = assigns.get_ref();
 
         // note there might be some trouble with mixed assignments
-        finish_interpretation(env, done_signal, assign_ref.iter())
+        self.finish(done_signal, assign_ref.iter())
+    }
+
+    /// Deconstructs the interpreter and concludes interpretation of `assigns`
+    /// with [finish_interpretation], handing it the buffers this interpreter
+    /// used to find destination cells. The interpreter must have finished
+    /// executing first
+    pub(crate) fn finish<'a, I, P>(
+        mut self,
+        done_signal: Option<P>,
+        assigns: I,
+    ) -> InterpreterResult<InterpreterState>
+    where
+        I: Iterator<Item = &'a Assignment<ir::Nothing>>,
+        P: Into<ArcTexOrConst<Port>>,
+    {
+        let mut scratch = std::mem::take(&mut self.dest_scratch);
+        let mut cells = std::mem::take(&mut self.dest_cells);
+        let env = self.deconstruct()?;
+        finish_interpretation(
+            env,
+            done_signal,
+            assigns,
+            &mut scratch,
+            &mut cells,
+        )
     }
 
     pub fn get<P: AsRaw<iir::Port>>(&self, port: P) -> &Value {
@@ -472,8 +558,17 @@ pub(crate) fn eval_prims<'a, 'b, I: Iterator<Item = &'b ArcTex<Cell>>>(
     exec_list: I,
     reset_flag: bool, // reset vals or execute normally
 ) -> InterpreterResult<bool> {
+    eval_prims_tracked(env, exec_list, reset_flag).map(|x| !x.is_empty())
+}
+
+/// The same as [eval_prims] but returns the ports whose values changed rather
+/// than a flag.
+fn eval_prims_tracked<'b, I: Iterator<Item = &'b ArcTex<Cell>>>(
+    env: &mut InterpreterState,
+    exec_list: I,
+    reset_flag: bool, // reset vals or execute normally
+) -> InterpreterResult<Vec<ArcTex<Port>>> {
     // dbg!("running eval prims");
-    let mut val_changed = false;
     // split mutability
     // TODO: change approach based on new env, once ready
     let ref_clone = env.cell_map.clone(); // RC clone
@@ -499,7 +594,9 @@ pub(crate) fn eval_prims<'a, 'b, I: Iterator<Item = &'b ArcTex<Cell>>>(
                 let current_val = env.get_from_port(&port_ref);
 
                 if *current_val != val {
-                    val_changed = true;
+                    if let Some(observer) = env.observer() {
+                        observer.on_cell_written(&cell.read(), port, &val);
+                    }
                     // defer value update until after all executions
                     update_list.push((Arc::clone(&port_ref), val));
                 }
@@ -507,11 +604,13 @@ pub(crate) fn eval_prims<'a, 'b, I: Iterator<Item = &'b ArcTex<Cell>>>(
         }
     }
 
+    let mut changed = Vec::with_capacity(update_list.len());
     for (port, val) in update_list {
-        env.insert(port, val);
+        env.insert(&port, val);
+        changed.push(port);
     }
 
-    Ok(val_changed)
+    Ok(changed)
 }
 
 fn get_inputs<'a>(
@@ -534,7 +633,9 @@ fn get_inputs<'a>(
 
 /// Concludes interpretation to a group, effectively setting the go signal low
 /// for a given group. This function updates the values in the environment
-/// accordingly using zero as a placeholder for values that are undefined
+/// accordingly using zero as a placeholder for values that are undefined. The
+/// cells to update are collected into `cells`, with `scratch` used to skip
+/// duplicates, so callers can reuse both
 pub(crate) fn finish_interpretation<
     'a,
     I: Iterator<Item = &'a Assignment<ir::Nothing>>,
@@ -543,6 +644,8 @@ pub(crate) fn finish_interpretation<
     mut env: InterpreterState,
     done_signal: Option<P>,
     assigns: I,
+    scratch: &mut HashSet<*const Cell>,
+    cells: &mut Vec<ArcTex<Cell>>,
 ) -> InterpreterResult<InterpreterState> {
     let done_signal: Option<ArcTexOrConst<Port>> =
         done_signal.map(|x| x.into());
@@ -556,9 +659,11 @@ pub(crate) fn finish_interpretation<
         );
     }
 
-    let cells = get_dest_cells(
+    get_dest_cells_into(
         assigns.iter().copied(),
         done_signal.as_ref().and_then(|x| x.as_arc().cloned()),
+        scratch,
+        cells,
     );
 
     if let Some(done_signal) = done_signal {
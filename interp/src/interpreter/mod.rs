@@ -4,8 +4,18 @@ mod component_interpreter;
 mod control_interpreter;
 mod group_interpreter;
 mod interpreter_trait;
+mod observer;
+mod program_interpreter;
+mod stats;
 mod utils;
 
 pub use component_interpreter::ComponentInterpreter;
+pub use control_interpreter::check_supported;
 pub use interpreter_trait::Interpreter;
-pub use utils::{ConstCell, ConstPort};
+pub use observer::InterpreterObserver;
+pub use program_interpreter::{ProgramInterpreter, StepResult};
+pub use stats::{GroupStats, RunStats};
+pub use utils::{
+    control_is_empty, format_value, get_dest_cells, get_dest_cells_into,
+    get_dest_cells_with_capacity, get_src_cells, ConstCell, ConstPort,
+};
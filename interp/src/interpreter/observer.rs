@@ -0,0 +1,40 @@
+//! Hooks for observing the interpreter as it runs
+
+use crate::interpreter_ir::{Assignment, Cell, Group};
+use crate::values::Value;
+use calyx_ir::{Id, Nothing};
+
+/// A set of callbacks invoked by the interpreter at key points during
+/// execution. Every method defaults to a no-op so implementors need only
+/// override the events they care about. Observers are registered through
+/// [crate::configuration::ConfigBuilder::observer].
+///
+/// Callbacks may be issued from multiple threads at once (assignments are
+/// evaluated in parallel), so any state an observer accumulates needs to be
+/// behind a lock or atomic.
+pub trait InterpreterObserver: Send + Sync {
+    /// Called when a group is enabled and begins executing
+    fn on_group_activated(&self, _group: &Group) {}
+
+    /// Called once for every clock cycle in which a group is active
+    fn on_group_cycle(&self, _group: &Group) {}
+
+    /// Called whenever an assignment's guard holds and its source value is
+    /// applied to the destination port
+    fn on_assignment_fired(
+        &self,
+        _assign: &Assignment<Nothing>,
+        _value: &Value,
+    ) {
+    }
+
+    /// Called whenever a primitive cell writes a new value to one of its
+    /// output ports
+    fn on_cell_written(&self, _cell: &Cell, _port: Id, _value: &Value) {}
+}
+
+impl std::fmt::Debug for dyn InterpreterObserver {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "<observer>")
+    }
+}
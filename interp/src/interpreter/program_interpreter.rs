@@ -0,0 +1,128 @@
+//! A resumable interface for driving a whole program one cycle at a time
+
+use std::sync::Arc;
+
+use super::{ComponentInterpreter, Interpreter};
+use crate::{
+    environment::InterpreterState,
+    errors::InterpreterResult,
+    interpreter_ir as iir,
+    structures::{
+        names::ComponentQualifiedInstanceName, state_views::StateView,
+    },
+    utils::{find_stimulus_port, StimulusMap},
+};
+
+/// The outcome of advancing a [ProgramInterpreter]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StepResult {
+    /// The program has not yet finished
+    Running,
+    /// The program has finished and may be [finished](ProgramInterpreter::finish)
+    Done,
+}
+
+/// An interpreter for an entire program which can be advanced incrementally
+/// rather than run to completion. This is intended for embedding the
+/// interpreter in interactive frontends.
+///
+/// The entry component's `go` signal is held high from construction until
+/// [ProgramInterpreter::finish] is called.
+pub struct ProgramInterpreter {
+    interp: ComponentInterpreter,
+    comp: Arc<iir::Component>,
+    stimulus: Option<Arc<StimulusMap>>,
+    cycles: u64,
+}
+
+impl ProgramInterpreter {
+    /// Prepares `comp` to be interpreted from the given initial state
+    pub fn new(env: InterpreterState, comp: &Arc<iir::Component>) -> Self {
+        let qin = ComponentQualifiedInstanceName::new_single(comp, comp.name);
+        let stimulus = env.stimulus().cloned();
+        let mut interp = ComponentInterpreter::from_component(comp, env, qin);
+        interp.set_go_high();
+        Self {
+            interp,
+            comp: Arc::clone(comp),
+            stimulus,
+            cycles: 0,
+        }
+    }
+
+    /// Advance the program by a single cycle. Does nothing if the program has
+    /// already finished. Any stimulus for the cycle is applied to the inputs
+    /// first.
+    pub fn step(&mut self) -> InterpreterResult<StepResult> {
+        if !self.interp.is_done() {
+            self.apply_stimulus()?;
+            self.interp.step()?;
+            self.cycles += 1;
+        }
+        Ok(self.status())
+    }
+
+    /// Advance the program by up to `n` cycles, stopping early if it finishes
+    pub fn step_n(&mut self, n: u64) -> InterpreterResult<StepResult> {
+        for _ in 0..n {
+            if self.step()? == StepResult::Done {
+                break;
+            }
+        }
+        Ok(self.status())
+    }
+
+    /// Run the program until it finishes, one [ProgramInterpreter::step] at a
+    /// time, so stimulus is applied and [ProgramInterpreter::cycles] counts
+    /// every cycle taken.
+    pub fn run(&mut self) -> InterpreterResult<()> {
+        while self.step()? == StepResult::Running {}
+        Ok(())
+    }
+
+    /// Returns whether the program has finished
+    pub fn is_done(&self) -> bool {
+        self.interp.is_done()
+    }
+
+    /// The number of cycles taken so far by any of the stepping methods
+    pub fn cycles(&self) -> u64 {
+        self.cycles
+    }
+
+    /// Returns a view of the current program state
+    pub fn state(&self) -> StateView<'_> {
+        self.interp.get_env()
+    }
+
+    /// Lowers the `go` signal and returns the final state of the program
+    ///
+    /// # Panics
+    /// If the program has not finished
+    pub fn finish(mut self) -> InterpreterResult<InterpreterState> {
+        self.interp.set_go_low();
+        self.interp.deconstruct()
+    }
+
+    fn apply_stimulus(&mut self) -> InterpreterResult<()> {
+        let Some(values) =
+            self.stimulus.as_ref().and_then(|s| s.get(self.cycles))
+        else {
+            return Ok(());
+        };
+
+        for (port, value) in values {
+            let target = find_stimulus_port(&self.comp, port)?;
+            self.interp.get_env_mut().insert(&target, value.clone());
+        }
+        Ok(())
+    }
+
+    fn status(&self) -> StepResult {
+        if self.interp.is_done() {
+            StepResult::Done
+        } else {
+            StepResult::Running
+        }
+    }
+}
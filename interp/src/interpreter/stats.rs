@@ -0,0 +1,53 @@
+//! Profiling counters for group activity
+
+use super::InterpreterObserver;
+use crate::interpreter_ir::Group;
+use calyx_ir::Id;
+use parking_lot::Mutex;
+use std::collections::HashMap;
+
+/// Activity counters for a single group
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct GroupStats {
+    /// The number of times the group was enabled
+    pub activations: u64,
+    /// The total number of cycles the group was active across all activations
+    pub cycles: u64,
+}
+
+/// An [InterpreterObserver] which tallies how often each group is entered and
+/// how many cycles it is active for. Register it with
+/// [crate::configuration::ConfigBuilder::observer] and read the results with
+/// [RunStats::group_stats] once the run is finished.
+///
+/// Groups are keyed by name, so same-named groups in different components
+/// share an entry.
+#[derive(Debug, Default)]
+pub struct RunStats {
+    groups: Mutex<HashMap<Id, GroupStats>>,
+}
+
+impl RunStats {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns a snapshot of the counters collected so far
+    pub fn group_stats(&self) -> HashMap<Id, GroupStats> {
+        self.groups.lock().clone()
+    }
+}
+
+impl InterpreterObserver for RunStats {
+    fn on_group_activated(&self, group: &Group) {
+        self.groups
+            .lock()
+            .entry(group.name())
+            .or_default()
+            .activations += 1;
+    }
+
+    fn on_group_cycle(&self, group: &Group) {
+        self.groups.lock().entry(group.name()).or_default().cycles += 1;
+    }
+}
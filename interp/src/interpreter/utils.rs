@@ -1,23 +1,52 @@
 use crate::{
-    interpreter_ir::{Assignment, Cell, Control, Group, Port, PortParent},
-    utils::ArcTex,
+    errors::{InterpreterError, InterpreterResult},
+    interpreter_ir::{
+        Assignment, Cell, Control, ControlVisitor, Enable, Group, If, Invoke,
+        Port, PortParent, Repeat, StaticControl, While,
+    },
+    utils::{ArcTex, PrintCode},
     values::Value,
 };
 use calyx_ir as orig_ir;
+use orig_ir::NumAttr;
 use std::cell::Ref;
 use std::collections::HashSet;
 use std::ops::Deref;
 pub type ConstPort = *const Port;
 pub type ConstCell = *const Cell;
 
+/// Finds the `done` hole of the group. A hole carrying the `@done` attribute
+/// is preferred, otherwise the hole named `done` is used.
 #[inline]
-pub fn get_done_port(group: &Group) -> ArcTex<Port> {
-    group.get("done")
+pub fn get_done_port(group: &Group) -> InterpreterResult<ArcTex<Port>> {
+    find_interface_hole(group, NumAttr::Done, "done")
 }
 
+/// Finds the `go` hole of the group. A hole carrying the `@go` attribute is
+/// preferred, otherwise the hole named `go` is used.
 #[inline]
-pub fn get_go_port(group: &Group) -> ArcTex<Port> {
-    group.get("go")
+pub fn get_go_port(group: &Group) -> InterpreterResult<ArcTex<Port>> {
+    find_interface_hole(group, NumAttr::Go, "go")
+}
+
+fn find_interface_hole(
+    group: &Group,
+    attr: NumAttr,
+    name: &str,
+) -> InterpreterResult<ArcTex<Port>> {
+    group
+        .holes
+        .iter()
+        .find(|hole| hole.read().has_attribute(attr))
+        .cloned()
+        .or_else(|| group.find(name))
+        .ok_or_else(|| {
+            InterpreterError::MissingGroupHole {
+                group: group.name(),
+                hole: name.to_string(),
+            }
+            .into()
+        })
 }
 
 #[inline]
@@ -25,6 +54,20 @@ pub fn is_signal_high(done: &Value) -> bool {
     done.as_bool()
 }
 
+/// Renders the given value as a string according to the print code. Fixed
+/// point codes carry the number of fractional bits, i.e. the position of the
+/// binary point counted from the least significant bit.
+pub fn format_value(value: &Value, code: PrintCode) -> String {
+    match code {
+        PrintCode::Binary => format!("{}", value),
+        PrintCode::Unsigned => format!("{}", value.as_unsigned()),
+        PrintCode::Signed => format!("{}", value.as_signed()),
+        PrintCode::Hex => format!("{:#x}", value.as_unsigned()),
+        PrintCode::UFixed(frac) => format!("{}", value.as_ufp(frac)),
+        PrintCode::SFixed(frac) => format!("{}", value.as_sfp(frac)),
+    }
+}
+
 pub fn get_dest_cells<'a, I>(
     iter: I,
     done_sig: Option<ArcTex<Port>>,
@@ -32,52 +75,140 @@ pub fn get_dest_cells<'a, I>(
 where
     I: Iterator<Item = &'a Assignment<orig_ir::Nothing>>,
 {
-    let mut assign_set: HashSet<*const Cell> = HashSet::new();
-    let mut output_vec = vec![];
+    get_dest_cells_with_capacity(iter, done_sig, 0)
+}
+
+/// As [get_dest_cells] but pre-allocates room for `capacity` cells.
+pub fn get_dest_cells_with_capacity<'a, I>(
+    iter: I,
+    done_sig: Option<ArcTex<Port>>,
+    capacity: usize,
+) -> Vec<ArcTex<Cell>>
+where
+    I: Iterator<Item = &'a Assignment<orig_ir::Nothing>>,
+{
+    let mut assign_set = HashSet::with_capacity(capacity);
+    let mut output_vec = Vec::with_capacity(capacity);
+    get_dest_cells_into(iter, done_sig, &mut assign_set, &mut output_vec);
+    output_vec
+}
+
+/// As [get_dest_cells] but writes the cells into `output` and uses `scratch`
+/// for deduplication so callers can reuse both allocations across calls.
+/// Both are cleared before use.
+pub fn get_dest_cells_into<'a, I>(
+    iter: I,
+    done_sig: Option<ArcTex<Port>>,
+    scratch: &mut HashSet<*const Cell>,
+    output: &mut Vec<ArcTex<Cell>>,
+) where
+    I: Iterator<Item = &'a Assignment<orig_ir::Nothing>>,
+{
+    scratch.clear();
+    output.clear();
 
     if let Some(done_prt) = done_sig {
         if let PortParent::Cell(c) = &done_prt.read().parent {
-            let parent = c.upgrade();
-            assign_set.insert(parent.data_ptr());
-            output_vec.push(parent)
+            if let Some(parent) = c.try_upgrade() {
+                scratch.insert(parent.data_ptr());
+                output.push(parent)
+            }
         }
     };
 
     let iterator = iter.filter_map(|assign| {
         match &assign.dst.read().parent {
             PortParent::Cell(c) => {
-                match &c.upgrade().read().prototype {
-                    orig_ir::CellType::Primitive { .. }
-                    | orig_ir::CellType::Constant { .. }
-                    | orig_ir::CellType::Component { .. } => {
-                        let const_cell: *const Cell = c.upgrade().data_ptr();
-                        if assign_set.contains(&const_cell) {
-                            None //b/c we don't want duplicates
-                        } else {
-                            assign_set.insert(const_cell);
-                            Some(c.upgrade())
-                        }
-                    }
-
-                    orig_ir::CellType::ThisComponent => None,
+                // cells which have been dropped have nothing to update
+                let cell = c.try_upgrade()?;
+                let is_this_component = matches!(
+                    cell.read().prototype,
+                    orig_ir::CellType::ThisComponent
+                );
+                // skip duplicates, the handle is moved out only when new
+                if is_this_component || !scratch.insert(cell.data_ptr()) {
+                    None
+                } else {
+                    Some(cell)
                 }
             }
-            PortParent::Group(_) => None,
+            PortParent::Group(_) | PortParent::StaticGroup(_) => None,
         }
     });
-    output_vec.extend(iterator);
+    output.extend(iterator);
+}
+
+/// The counterpart of [get_dest_cells]: returns the cells which feed the
+/// given assignments, either through the source port or through a port read
+/// by the guard. Cells appear once each in the order first encountered, and
+/// the enclosing component's signature is skipped.
+pub fn get_src_cells<'a, I>(iter: I) -> Vec<ArcTex<Cell>>
+where
+    I: Iterator<Item = &'a Assignment<orig_ir::Nothing>>,
+{
+    let mut seen: HashSet<*const Cell> = HashSet::new();
+    let mut output_vec = vec![];
+
+    for assign in iter {
+        let ports = std::iter::once(assign.src.clone())
+            .chain(assign.guard.referenced_ports());
+        for port in ports {
+            let PortParent::Cell(c) = &port.read().parent else {
+                continue;
+            };
+            // cells which have been dropped feed nothing
+            let Some(cell) = c.try_upgrade() else {
+                continue;
+            };
+            let is_this_component = matches!(
+                cell.read().prototype,
+                orig_ir::CellType::ThisComponent
+            );
+            if !is_this_component && seen.insert(cell.data_ptr()) {
+                output_vec.push(cell);
+            }
+        }
+    }
 
     output_vec
 }
+
 pub fn control_is_empty(control: &Control) -> bool {
-    match control {
-        Control::Seq(s) => s.stmts.iter().all(control_is_empty),
-        Control::Par(p) => p.stmts.iter().all(control_is_empty),
-        Control::If(_) => false,
-        Control::While(_) => false,
-        Control::Invoke(_) => false,
-        Control::Enable(_) => false,
-        Control::Empty(_) => true,
+    let mut visitor = EmptyVisitor(true);
+    visitor.walk(control);
+    visitor.0
+}
+
+/// Clears its flag upon reaching any node which does work
+struct EmptyVisitor(bool);
+
+impl ControlVisitor for EmptyVisitor {
+    fn visit_if(&mut self, _if: &If) {
+        self.0 = false
+    }
+
+    fn visit_while(&mut self, _while: &While) {
+        self.0 = false
+    }
+
+    fn visit_repeat(&mut self, repeat: &Repeat) {
+        if repeat.num_repeats != 0 {
+            self.walk(&repeat.body)
+        }
+    }
+
+    fn visit_invoke(&mut self, _invoke: &Invoke) {
+        self.0 = false
+    }
+
+    fn visit_enable(&mut self, _enable: &Enable) {
+        self.0 = false
+    }
+
+    fn visit_static(&mut self, control: &StaticControl) {
+        if !matches!(control, StaticControl::Empty(_)) {
+            self.0 = false
+        }
     }
 }
 
@@ -98,6 +229,20 @@ impl<'a, T> From<Ref<'a, T>> for ReferenceHolder<'a, T> {
     }
 }
 
+impl<'a, T> ReferenceHolder<'a, T> {
+    /// Projects the held reference to a component of the borrowed data,
+    /// keeping the underlying borrow alive if there is one.
+    pub fn map<U, F>(self, f: F) -> ReferenceHolder<'a, U>
+    where
+        F: FnOnce(&T) -> &U,
+    {
+        match self {
+            ReferenceHolder::Ref(r) => ReferenceHolder::Ref(Ref::map(r, f)),
+            ReferenceHolder::Borrow(b) => ReferenceHolder::Borrow(f(b)),
+        }
+    }
+}
+
 impl<'a, T> Deref for ReferenceHolder<'a, T> {
     type Target = T;
 
@@ -0,0 +1,199 @@
+use std::collections::HashSet;
+use std::sync::Arc;
+
+use calyx_frontend::Attributes;
+use calyx_ir::{CellType, Direction, Nothing};
+use calyx_utils::Id;
+
+use crate::errors::{InterpreterError, InterpreterResult};
+use crate::utils::{arctex, ArcTex, WeakArcTex};
+
+use super::{
+    Assignment, Cell, CombGroup, Component, Control, ControlVisitor, Empty,
+    Enable, Group, Port, PortParent,
+};
+
+/// Assembles a [Component] directly, without going through a
+/// [calyx_ir::Component]. This is mostly useful for building small components
+/// in tests.
+///
+/// Cells, groups and ports are handed back as they are added so they can be
+/// used to write assignments and control.
+pub struct ComponentBuilder {
+    name: Id,
+    signature: ArcTex<Cell>,
+    cells: Vec<ArcTex<Cell>>,
+    groups: Vec<ArcTex<Group>>,
+    comb_groups: Vec<ArcTex<CombGroup>>,
+    continuous_assignments: Vec<Assignment<Nothing>>,
+    control: Control,
+    attributes: Attributes,
+    /// The next id to hand out to a cell or port
+    next_uid: u32,
+}
+
+impl ComponentBuilder {
+    /// Starts a component with an empty signature and empty control
+    pub fn new<S: Into<Id>>(name: S) -> Self {
+        let name = name.into();
+        Self {
+            name,
+            signature: arctex(Cell::new(name, CellType::ThisComponent)),
+            cells: vec![],
+            groups: vec![],
+            comb_groups: vec![],
+            continuous_assignments: vec![],
+            control: Control::Empty(Arc::new(Empty {
+                attributes: Default::default(),
+            })),
+            attributes: Default::default(),
+            next_uid: 1,
+        }
+    }
+
+    /// Adds an input port to the signature. As in the Calyx IR, the port is
+    /// an output of the signature cell since the component reads from it.
+    pub fn add_input<S: Into<Id>>(
+        &mut self,
+        name: S,
+        width: u64,
+    ) -> ArcTex<Port> {
+        let uid = self.next_uid();
+        add_port(&self.signature, name.into(), width, Direction::Output, uid)
+    }
+
+    /// Adds an output port to the signature. As in the Calyx IR, the port is
+    /// an input of the signature cell since the component writes to it.
+    pub fn add_output<S: Into<Id>>(
+        &mut self,
+        name: S,
+        width: u64,
+    ) -> ArcTex<Port> {
+        let uid = self.next_uid();
+        add_port(&self.signature, name.into(), width, Direction::Input, uid)
+    }
+
+    /// Adds a cell with the given ports, each given as `(name, width,
+    /// direction)`
+    pub fn add_cell<S: Into<Id>>(
+        &mut self,
+        name: S,
+        prototype: CellType,
+        ports: &[(&str, u64, Direction)],
+    ) -> ArcTex<Cell> {
+        let mut cell = Cell::new(name.into(), prototype);
+        cell.uid = self.next_uid();
+        let cell = arctex(cell);
+        for (port, width, dir) in ports {
+            let uid = self.next_uid();
+            add_port(&cell, (*port).into(), *width, dir.clone(), uid);
+        }
+        self.cells.push(cell.clone());
+        cell
+    }
+
+    /// Adds a group with `go` and `done` holes and no assignments
+    pub fn add_group<S: Into<Id>>(&mut self, name: S) -> ArcTex<Group> {
+        let group = arctex(Group::new(name.into()));
+        let parent: PortParent = WeakArcTex::from(&group).into();
+        let holes = ["go", "done"].map(|hole| {
+            arctex(Port {
+                name: hole.into(),
+                width: 1,
+                direction: Direction::Inout,
+                parent: parent.clone(),
+                attributes: Default::default(),
+                uid: self.next_uid(),
+            })
+        });
+        group.write().holes.extend(holes);
+        self.groups.push(group.clone());
+        group
+    }
+
+    /// Adds a combinational group with the given assignments
+    pub fn add_comb_group<S: Into<Id>>(
+        &mut self,
+        name: S,
+        assignments: Vec<Assignment<Nothing>>,
+    ) -> ArcTex<CombGroup> {
+        let group = arctex(CombGroup {
+            name: name.into(),
+            assignments,
+            attributes: Default::default(),
+        });
+        self.comb_groups.push(group.clone());
+        group
+    }
+
+    pub fn add_continuous_assignment(&mut self, assign: Assignment<Nothing>) {
+        self.continuous_assignments.push(assign)
+    }
+
+    pub fn set_control(&mut self, control: Control) {
+        self.control = control
+    }
+
+    pub fn set_attributes(&mut self, attributes: Attributes) {
+        self.attributes = attributes
+    }
+
+    /// Finishes the component. Errors if the control enables a group which
+    /// was not added to this builder.
+    pub fn build(self) -> InterpreterResult<Component> {
+        let defined: HashSet<Id> =
+            self.groups.iter().map(|g| g.read().name()).collect();
+        let mut enabled = EnabledGroups(vec![]);
+        enabled.walk(&self.control);
+        if let Some(missing) =
+            enabled.0.into_iter().find(|name| !defined.contains(name))
+        {
+            return Err(InterpreterError::UndefinedGroupEnable(missing).into());
+        }
+
+        Ok(Component {
+            name: self.name,
+            signature: self.signature,
+            cells: self.cells.into(),
+            groups: self.groups.into(),
+            comb_groups: self.comb_groups.into(),
+            continuous_assignments: Arc::new(self.continuous_assignments),
+            control: self.control,
+            attributes: self.attributes,
+        })
+    }
+
+    fn next_uid(&mut self) -> u32 {
+        let uid = self.next_uid;
+        self.next_uid += 1;
+        uid
+    }
+}
+
+fn add_port(
+    cell: &ArcTex<Cell>,
+    name: Id,
+    width: u64,
+    direction: Direction,
+    uid: u32,
+) -> ArcTex<Port> {
+    let port = arctex(Port {
+        name,
+        width,
+        direction,
+        parent: WeakArcTex::from(cell).into(),
+        attributes: Default::default(),
+        uid,
+    });
+    cell.write().ports.push(port.clone());
+    port
+}
+
+/// Collects the names of the groups enabled by a control program
+struct EnabledGroups(Vec<Id>);
+
+impl ControlVisitor for EnabledGroups {
+    fn visit_enable(&mut self, enable: &Enable) {
+        self.0.push(enable.group.read().name())
+    }
+}
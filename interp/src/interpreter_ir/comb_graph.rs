@@ -0,0 +1,341 @@
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::sync::Arc;
+
+use calyx_ir::{CellType, Direction, Nothing};
+use itertools::Itertools;
+use petgraph::algo::{kosaraju_scc, toposort};
+use petgraph::graph::{DiGraph, NodeIndex};
+use petgraph::visit::Bfs;
+
+use crate::errors::{InterpreterError, InterpreterResult};
+use crate::utils::{ArcTex, AsRaw};
+
+use super::{Assignment, Cell, Component, Group, Port, PortParent};
+
+/// A port-level dataflow graph of the combinational paths in a component.
+///
+/// Edges run from every port an assignment reads (its source and the ports in
+/// its guard) to the assignment's destination, and from the inputs to the
+/// outputs of combinational primitives. Stateful cells contribute no internal
+/// edges, so any path through a register is cut.
+#[derive(Clone)]
+pub(crate) struct CombGraph {
+    graph: DiGraph<ArcTex<Port>, ()>,
+    nodes: HashMap<*const Port, NodeIndex>,
+}
+
+impl CombGraph {
+    /// Build the graph from the continuous assignments and the assignments of
+    /// every combinational group in `comp`.
+    pub(crate) fn from_component(comp: &Component) -> Self {
+        let mut out = Self {
+            graph: DiGraph::new(),
+            nodes: HashMap::new(),
+        };
+
+        for assign in comp.continuous_assignments.iter() {
+            out.add_assignment(assign);
+        }
+
+        for comb_group in comp.comb_groups.iter() {
+            let comb_group = comb_group.read();
+            for assign in comb_group.assignments.iter() {
+                out.add_assignment(assign);
+            }
+        }
+
+        for cell in comp.cells.iter() {
+            let cell = cell.read();
+            if let CellType::Primitive { is_comb: true, .. } = &cell.prototype {
+                let (inputs, outputs): (Vec<_>, Vec<_>) = cell
+                    .ports
+                    .iter()
+                    .filter(|p| p.read().direction != Direction::Inout)
+                    .partition(|p| p.read().direction == Direction::Input);
+
+                for input in &inputs {
+                    for output in &outputs {
+                        out.add_edge(input, output);
+                    }
+                }
+            }
+        }
+
+        out
+    }
+
+    /// Returns true if `to` can be reached from `from` along combinational
+    /// edges. A port is always reachable from itself.
+    pub(crate) fn is_reachable(
+        &self,
+        from: &ArcTex<Port>,
+        to: &ArcTex<Port>,
+    ) -> bool {
+        let from: *const Port = from.as_raw();
+        let to: *const Port = to.as_raw();
+
+        if from == to {
+            return true;
+        }
+
+        let (Some(&start), Some(&end)) =
+            (self.nodes.get(&from), self.nodes.get(&to))
+        else {
+            return false;
+        };
+
+        let mut bfs = Bfs::new(&self.graph, start);
+        while let Some(node) = bfs.next(&self.graph) {
+            if node == end {
+                return true;
+            }
+        }
+        false
+    }
+
+    /// The number of edges on the longest path through the graph, or `None`
+    /// if the graph contains a cycle.
+    pub(crate) fn longest_path(&self) -> Option<usize> {
+        let order = toposort(&self.graph, None).ok()?;
+        let mut depth = vec![0_usize; self.graph.node_count()];
+
+        for node in order {
+            for succ in self.graph.neighbors(node) {
+                depth[succ.index()] =
+                    depth[succ.index()].max(depth[node.index()] + 1);
+            }
+        }
+
+        Some(depth.into_iter().max().unwrap_or_default())
+    }
+
+    /// Returns a port which lies on a combinational cycle, or `None` if the
+    /// graph is acyclic.
+    pub(crate) fn find_cycle(&self) -> Option<ArcTex<Port>> {
+        toposort(&self.graph, None)
+            .err()
+            .map(|cycle| Arc::clone(&self.graph[cycle.node_id()]))
+    }
+
+    /// Returns the ports along some combinational cycle, beginning and ending
+    /// with the same port, or `None` if the graph is acyclic.
+    pub(crate) fn cycle_path(&self) -> Option<Vec<ArcTex<Port>>> {
+        find_cycle_path(&self.graph).map(|path| {
+            path.into_iter()
+                .map(|n| Arc::clone(&self.graph[n]))
+                .collect()
+        })
+    }
+
+    /// Errors with the canonical names of the ports along a cycle if the
+    /// continuous assignments, combinational groups, or any single group of
+    /// `comp` form a combinational loop. Groups are checked one at a time
+    /// since the assignments of different groups are not active together
+    /// unless run in parallel.
+    pub(crate) fn check_cycles(comp: &Component) -> InterpreterResult<()> {
+        let base = Self::from_component(comp);
+        let report = |path: Vec<ArcTex<Port>>| -> InterpreterResult<()> {
+            let names = path
+                .iter()
+                .map(|p| p.read().canonical().to_string())
+                .collect();
+            Err(InterpreterError::CombinationalCycle(names).into())
+        };
+
+        if let Some(path) = base.cycle_path() {
+            return report(path);
+        }
+
+        for group in comp.groups.iter() {
+            let mut graph = base.clone();
+            for assign in group.read().assignments.iter() {
+                graph.add_assignment(assign);
+            }
+            if let Some(path) = graph.cycle_path() {
+                return report(path);
+            }
+        }
+
+        Ok(())
+    }
+
+    fn add_assignment(&mut self, assign: &Assignment<Nothing>) {
+        self.add_edge(&assign.src, &assign.dst);
+
+        for port in assign.guard.referenced_ports() {
+            self.add_edge(&port, &assign.dst);
+        }
+    }
+
+    fn add_edge(&mut self, from: &ArcTex<Port>, to: &ArcTex<Port>) {
+        let from = self.node(from);
+        let to = self.node(to);
+        self.graph.update_edge(from, to, ());
+    }
+
+    fn node(&mut self, port: &ArcTex<Port>) -> NodeIndex {
+        let key: *const Port = port.as_raw();
+        let graph = &mut self.graph;
+        *self
+            .nodes
+            .entry(key)
+            .or_insert_with(|| graph.add_node(Arc::clone(port)))
+    }
+}
+
+/// Orders the cells of `comp` so that every combinational cell comes after
+/// the cells which feed it while `group` runs. Only the assignments active
+/// together are considered: the continuous assignments and those of the
+/// combinational groups, as in [CombGraph::check_cycles], plus those of
+/// `group` if one is given. An edge is added from the parent of each port an
+/// assignment reads to the parent of its destination, but only when the
+/// source is a combinational primitive, since the outputs of stateful cells
+/// and subcomponents do not change within a cycle. Ports whose parent has
+/// been dropped are skipped. Errors with the cells along a cycle if these
+/// assignments form a combinational loop.
+pub(crate) fn topo_order_cells(
+    comp: &Component,
+    group: Option<&ArcTex<Group>>,
+) -> InterpreterResult<Vec<ArcTex<Cell>>> {
+    let mut graph: DiGraph<ArcTex<Cell>, ()> = DiGraph::new();
+    let nodes: HashMap<*const Cell, NodeIndex> = comp
+        .cells
+        .iter()
+        .map(|cell| {
+            (cell.data_ptr() as *const Cell, graph.add_node(cell.clone()))
+        })
+        .collect();
+
+    let node_of = |port: &ArcTex<Port>| match &port.read().parent {
+        PortParent::Cell(c) => {
+            let key: *const Cell = c.try_upgrade()?.data_ptr();
+            nodes.get(&key).copied()
+        }
+        PortParent::Group(_) | PortParent::StaticGroup(_) => None,
+    };
+
+    let mut add_assignment = |assign: &Assignment<Nothing>| {
+        let Some(dst) = node_of(&assign.dst) else {
+            return;
+        };
+        let reads = std::iter::once(assign.src.clone())
+            .chain(assign.guard.referenced_ports());
+        for src in reads.filter_map(|p| node_of(&p)) {
+            let is_comb = matches!(
+                graph[src].read().prototype,
+                CellType::Primitive { is_comb: true, .. }
+            );
+            if is_comb {
+                graph.update_edge(src, dst, ());
+            }
+        }
+    };
+
+    for assign in comp.continuous_assignments.iter() {
+        add_assignment(assign);
+    }
+    for comb_group in comp.comb_groups.iter() {
+        comb_group
+            .read()
+            .assignments
+            .iter()
+            .for_each(&mut add_assignment);
+    }
+    if let Some(group) = group {
+        group
+            .read()
+            .assignments
+            .iter()
+            .for_each(&mut add_assignment);
+    }
+
+    match toposort(&graph, None) {
+        Ok(order) => Ok(order.into_iter().map(|n| graph[n].clone()).collect()),
+        Err(_) => {
+            let cycle = find_cycle_path(&graph)
+                .unwrap_or_default()
+                .into_iter()
+                .map(|n| graph[n].read().name().to_string())
+                .collect();
+            Err(InterpreterError::CombinationalCycle(cycle).into())
+        }
+    }
+}
+
+/// Orders `assigns` so that every assignment comes after those whose
+/// destinations it reads, either as its source or in its guard. Only
+/// dependencies within `assigns` are considered. Errors with the destinations
+/// along a cycle if the assignments read their own outputs.
+pub(crate) fn order_assignments(
+    assigns: &[Assignment<Nothing>],
+) -> InterpreterResult<Vec<&Assignment<Nothing>>> {
+    let mut graph: DiGraph<&Assignment<Nothing>, ()> = DiGraph::new();
+    let nodes = assigns.iter().map(|a| graph.add_node(a)).collect_vec();
+
+    let mut writers: HashMap<*const Port, Vec<NodeIndex>> = HashMap::new();
+    for (assign, node) in assigns.iter().zip(&nodes) {
+        writers.entry(assign.dst.as_raw()).or_default().push(*node);
+    }
+
+    for (assign, &node) in assigns.iter().zip(&nodes) {
+        let reads = std::iter::once(assign.src.clone())
+            .chain(assign.guard.referenced_ports());
+        for port in reads {
+            let key: *const Port = port.as_raw();
+            for &writer in writers.get(&key).into_iter().flatten() {
+                graph.update_edge(writer, node, ());
+            }
+        }
+    }
+
+    match toposort(&graph, None) {
+        Ok(order) => Ok(order.into_iter().map(|n| graph[n]).collect()),
+        Err(_) => {
+            let cycle = find_cycle_path(&graph)
+                .unwrap_or_default()
+                .into_iter()
+                .map(|n| graph[n].dst.read().canonical().to_string())
+                .collect();
+            Err(InterpreterError::CombinationalCycle(cycle).into())
+        }
+    }
+}
+
+/// Returns the nodes along some cycle in `graph`, beginning and ending with
+/// the same node, or `None` if the graph is acyclic.
+pub(crate) fn find_cycle_path<N>(
+    graph: &DiGraph<N, ()>,
+) -> Option<Vec<NodeIndex>> {
+    let scc = kosaraju_scc(graph)
+        .into_iter()
+        .find(|scc| scc.len() > 1 || graph.contains_edge(scc[0], scc[0]))?;
+    let members: HashSet<NodeIndex> = scc.iter().copied().collect();
+    let start = scc[0];
+
+    // breadth first search from the start until an edge leads back to it
+    let mut pred: HashMap<NodeIndex, NodeIndex> = HashMap::new();
+    let mut queue = VecDeque::from([start]);
+    let mut last = None;
+    'search: while let Some(node) = queue.pop_front() {
+        for succ in graph.neighbors(node) {
+            if succ == start {
+                last = Some(node);
+                break 'search;
+            }
+            if members.contains(&succ) && !pred.contains_key(&succ) {
+                pred.insert(succ, node);
+                queue.push_back(succ);
+            }
+        }
+    }
+
+    let mut path = vec![start];
+    let mut node = last?;
+    while node != start {
+        path.push(node);
+        node = pred[&node];
+    }
+    path.push(start);
+    path.reverse();
+    Some(path)
+}
@@ -1,17 +1,30 @@
-use crate::utils::ArcTex;
+use crate::{
+    errors::{InterpreterError, InterpreterResult},
+    utils::{ArcTex, AsRaw},
+};
 
 use super::{
-    control::Control, translator::TranslationMap, Assignment, Cell, CombGroup,
-    Group,
+    comb_graph::{self, CombGraph},
+    control::{Control, ControlVisitor, Invoke},
+    deep_clone::CloneMap,
+    structural_hash::StructuralHasher,
+    translator::TranslationMap,
+    validation::{self, ValidationWarning},
+    Assignment, Cell, CombGroup, Group, Port,
 };
-use calyx_frontend::Attributes;
-use calyx_ir::{Component as CalyxComponent, Nothing};
+use calyx_frontend::{Attribute, Attributes, NumAttr};
+use calyx_ir::{CellType, Component as CalyxComponent, Direction, Nothing};
 
 use calyx_utils::{GetName, Id};
 use itertools::Itertools;
-use linked_hash_map::LinkedHashMap;
+use std::collections::HashMap;
 use std::sync::Arc;
 
+/// A translated Calyx component. The cells, ports, and groups are held behind
+/// shared [ArcTex] handles, so a component is deliberately not [Clone]. Use
+/// [Component::clone_shallow] for a second handle onto the same structures,
+/// where changes through either are visible through both, and
+/// [Component::deep_clone] for a copy which shares nothing.
 #[derive(Debug)]
 pub struct Component {
     /// Name of the component.
@@ -42,6 +55,306 @@ impl Component {
         self.cells.find(name)
     }
 
+    /// Return a reference to the group with `name` if present.
+    pub fn find_group<S>(&self, name: S) -> Option<ArcTex<Group>>
+    where
+        S: Into<Id>,
+    {
+        self.groups.find(name)
+    }
+
+    /// Return a reference to the combinational group with `name` if present.
+    pub fn find_comb_group<S>(&self, name: S) -> Option<ArcTex<CombGroup>>
+    where
+        S: Into<Id>,
+    {
+        self.comb_groups.find(name)
+    }
+
+    /// Checks that every group has been completed by the translator. A group
+    /// is created without holes or assignments and filled in by a second
+    /// pass, so a group lacking its `go` or `done` hole was never completed.
+    /// A group with both holes but no assignments is taken to be empty on
+    /// purpose. Errors with the name of the first incomplete group.
+    pub fn assert_fully_translated(&self) -> InterpreterResult<()> {
+        for group in self.groups.iter() {
+            let group = group.read();
+            let has_hole = |name: &str, attr: NumAttr| {
+                group.holes.iter().any(|hole| {
+                    let hole = hole.read();
+                    hole.name == name || hole.has_attribute(attr)
+                })
+            };
+            if !(has_hole("go", NumAttr::Go) && has_hole("done", NumAttr::Done))
+            {
+                return Err(InterpreterError::PartiallyTranslatedGroup(
+                    group.name(),
+                )
+                .into());
+            }
+        }
+        Ok(())
+    }
+
+    /// Iterates over the `ref` cells of this component
+    pub fn reference_cells(&self) -> impl Iterator<Item = &ArcTex<Cell>> {
+        self.cells.iter().filter(|cell| cell.read().is_reference())
+    }
+
+    /// Iterates over the primitive cells of this component, giving the name of
+    /// each cell, the primitive it instantiates, and its parameter bindings.
+    /// Constants, subcomponents, and the signature are skipped.
+    pub fn primitives(
+        &self,
+    ) -> impl Iterator<Item = (Id, Id, Vec<(Id, u64)>)> + '_ {
+        self.cells.iter().filter_map(|cell| {
+            let cell = cell.read();
+            let primitive = cell.primitive_name()?;
+            Some((cell.name(), primitive, cell.parameters().collect()))
+        })
+    }
+
+    /// Checks every `invoke` in the control program which passes `ref` cells.
+    /// Each name the invoke binds must be a `ref` cell of the invoked
+    /// component. Invokes of components missing from `ctx` are skipped.
+    pub fn check_ref_cells(
+        &self,
+        ctx: &[Arc<Component>],
+    ) -> InterpreterResult<()> {
+        let mut checker = RefCellChecker {
+            ctx,
+            result: Ok(()),
+        };
+        checker.walk(&self.control);
+        checker.result
+    }
+
+    /// Sorts the ports of the signature and of every cell with
+    /// [Cell::sort_ports], so that port lists no longer depend on the order
+    /// the translator visited them in. Nothing is sorted unless this is
+    /// called. It should be called before anything records the position of a
+    /// port within [Cell::ports], since those positions change.
+    pub fn sort_ports(&self) {
+        self.signature.write().sort_ports();
+        self.cells.write_all(Cell::sort_ports);
+    }
+
+    /// Iterates over the input ports of this component. As in the Calyx IR,
+    /// directions on the signature are from the perspective of the
+    /// component's body, which reads its inputs, so these are the signature
+    /// ports with [Direction::Output].
+    pub fn input_ports(&self) -> impl Iterator<Item = ArcTex<Port>> {
+        self.signature_ports(Direction::Output)
+    }
+
+    /// Iterates over the output ports of this component. These are the
+    /// signature ports with [Direction::Input], see
+    /// [Component::input_ports].
+    pub fn output_ports(&self) -> impl Iterator<Item = ArcTex<Port>> {
+        self.signature_ports(Direction::Input)
+    }
+
+    fn signature_ports(
+        &self,
+        direction: Direction,
+    ) -> impl Iterator<Item = ArcTex<Port>> {
+        self.signature
+            .read()
+            .ports()
+            .clone()
+            .into_iter()
+            .filter(move |port| port.read().direction == direction)
+    }
+
+    /// Iterates over every port in this component: the signature ports, the
+    /// ports of each cell, and the holes of each group. Ports are collected
+    /// one cell or group at a time as the iterator advances.
+    pub fn all_ports(&self) -> impl Iterator<Item = ArcTex<Port>> + '_ {
+        std::iter::once(&self.signature)
+            .chain(self.cells.iter())
+            .flat_map(|cell| cell.read().ports().clone())
+            .chain(
+                self.groups
+                    .iter()
+                    .flat_map(|group| group.read().holes.clone()),
+            )
+    }
+
+    /// Iterates over every assignment in this component: the continuous
+    /// assignments, then those of each group, then those of each
+    /// combinational group. Assignments behind a lock are cloned one group
+    /// at a time as the iterator advances rather than yielded by reference,
+    /// so no lock is held between calls to `next`. Cloning an assignment
+    /// only copies its handles, not the ports or guard they point to.
+    pub fn all_assignments(
+        &self,
+    ) -> impl Iterator<Item = Assignment<Nothing>> + '_ {
+        self.continuous_assignments
+            .iter()
+            .cloned()
+            .chain(
+                self.groups
+                    .iter()
+                    .flat_map(|group| group.read().assignments.clone()),
+            )
+            .chain(
+                self.comb_groups
+                    .iter()
+                    .flat_map(|group| group.read().assignments.clone()),
+            )
+    }
+
+    /// Returns every assignment, from any of the sources visited by
+    /// [Component::all_assignments], whose destination is `port`. Ports are
+    /// compared by identity rather than by name.
+    pub fn drivers_of(&self, port: &ArcTex<Port>) -> Vec<Assignment<Nothing>> {
+        let target: *const Port = port.as_raw();
+        self.all_assignments()
+            .filter(|assign| assign.dst.as_raw() == target)
+            .collect()
+    }
+
+    /// Finds the port named by a string of the form `parent.port`, where the
+    /// parent is a cell, a group, or the component's signature (named either
+    /// by the component or by the signature cell). Since cell and port names
+    /// may themselves contain dots, each dot is tried as the separator from
+    /// left to right and the first split which names an existing port is
+    /// used.
+    pub fn resolve_canonical(&self, s: &str) -> Option<ArcTex<Port>> {
+        s.match_indices('.').find_map(|(idx, _)| {
+            let (parent, port) = (&s[..idx], &s[idx + 1..]);
+            if self.name == parent || self.signature.read().name() == parent {
+                if let Some(p) = self.signature.read().find(port) {
+                    return Some(p);
+                }
+            }
+            if let Some(p) =
+                self.find_cell(parent).and_then(|c| c.read().find(port))
+            {
+                return Some(p);
+            }
+            self.find_group(parent).and_then(|g| g.read().find(port))
+        })
+    }
+
+    /// Returns true if there is a path from `a` to `b` through the
+    /// combinational assignments of this component (continuous assignments
+    /// and combinational groups) which does not pass through a stateful cell.
+    pub fn combinationally_connected(
+        &self,
+        a: &ArcTex<Port>,
+        b: &ArcTex<Port>,
+    ) -> bool {
+        CombGraph::from_component(self).is_reachable(a, b)
+    }
+
+    /// Returns the cells of this component ordered so that producers come
+    /// before the combinational cells which consume them while `group` runs.
+    /// Edges are taken from the continuous assignments, the assignments of
+    /// the combinational groups, and those of `group` if given. The
+    /// assignments of different groups are not combined, since they are only
+    /// active together when run in parallel. Errors if these assignments
+    /// form a combinational loop.
+    pub fn topo_order_cells(
+        &self,
+        group: Option<&ArcTex<Group>>,
+    ) -> InterpreterResult<Vec<ArcTex<Cell>>> {
+        comb_graph::topo_order_cells(self, group)
+    }
+
+    /// Checks this component for combinational loops, considering the
+    /// continuous assignments and combinational groups together with the
+    /// assignments of each group in turn. Errors with the canonical names of
+    /// the ports along the first cycle found.
+    pub fn check_comb_cycles(&self) -> InterpreterResult<()> {
+        CombGraph::check_cycles(self)
+    }
+
+    /// Returns the length of the longest chain of combinational edges in this
+    /// component, counting both assignments and passes through combinational
+    /// primitives. This is a rough proxy for the critical path. Chains start
+    /// and end at ports with no combinational drivers or readers, such as
+    /// inputs and register outputs. If the component contains a combinational
+    /// cycle the depth is unbounded and `usize::MAX` is returned.
+    pub fn max_comb_depth(&self) -> usize {
+        CombGraph::from_component(self)
+            .longest_path()
+            .unwrap_or(usize::MAX)
+    }
+
+    /// The number of cycles the control program of this component takes, or
+    /// `None` if it contains any dynamic control. See
+    /// [Control::static_latency].
+    pub fn total_static_latency(&self) -> Option<u64> {
+        self.control.static_latency()
+    }
+
+    /// Return all continuous assignments that have the attribute `attr`.
+    pub fn continuous_assignments_with_attr<A>(
+        &self,
+        attr: A,
+    ) -> impl Iterator<Item = &Assignment<Nothing>> + '_
+    where
+        A: Into<Attribute>,
+    {
+        let attr = attr.into();
+        self.continuous_assignments
+            .iter()
+            .filter(move |a| a.attributes.has(attr))
+    }
+
+    /// Return the ports of every cell in this component that have the
+    /// attribute `attr`.
+    pub fn ports_with_attr<A>(&self, attr: A) -> Vec<ArcTex<Port>>
+    where
+        A: Into<Attribute>,
+    {
+        let attr = attr.into();
+        self.cells
+            .iter()
+            .flat_map(|cell| cell.read().find_all_with_attr(attr).collect_vec())
+            .collect()
+    }
+
+    /// Return the holes of every group in this component that have the
+    /// attribute `attr`.
+    pub fn holes_with_attr<A>(&self, attr: A) -> Vec<ArcTex<Port>>
+    where
+        A: Into<Attribute>,
+    {
+        let attr = attr.into();
+        self.groups
+            .iter()
+            .flat_map(|group| {
+                group
+                    .read()
+                    .holes
+                    .iter()
+                    .filter(|p| p.read().attributes.has(attr))
+                    .cloned()
+                    .collect_vec()
+            })
+            .collect()
+    }
+
+    /// Runs all structural checks on this component and returns every
+    /// problem found rather than stopping at the first. The checks cover
+    /// mismatched assignment widths, ports with multiple unconditional
+    /// drivers, undriven cell inputs, and combinational cycles.
+    pub fn validate(&self) -> InterpreterResult<Vec<ValidationWarning>> {
+        Ok(validation::validate(self))
+    }
+
+    /// Checks that no port is driven by two assignments which are always
+    /// active at the same time, i.e. whose guards simplify to `1`. Only
+    /// assignments which can be active together are compared: the
+    /// continuous assignments with each other and with those of each group
+    /// and comb group. Errors with the port and the first two conflicting
+    /// assignments found.
+    pub fn check_single_driver(&self) -> InterpreterResult<()> {
+        validation::check_single_driver(self)
+    }
+
     pub fn from_ir(
         cc: &CalyxComponent,
         translator: &mut TranslationMap,
@@ -63,14 +376,72 @@ impl Component {
                     .collect_vec(),
             ),
             control: Control::from_ir(&cc.control.borrow(), translator),
-            attributes: cc.attributes.clone(),
+            attributes: translator.translate_attributes(&cc.attributes),
+        }
+    }
+
+    /// Hashes the structure of this component: cell prototypes, parameters,
+    /// and ports, group assignments by the canonical names of their ports
+    /// and the shape of their guards, and the shape of the control program.
+    /// Components translated from the same source hash equally, as the hash
+    /// depends on names rather than addresses. Attributes are ignored. The
+    /// hash is stable within a build of the interpreter but is not
+    /// guaranteed to be across Rust versions.
+    pub fn structural_hash(&self) -> u64 {
+        StructuralHasher::hash_component(self)
+    }
+
+    /// Returns a component sharing every cell, port, group, and control node
+    /// with this one. This is cheap, since only the handles are cloned, but
+    /// the result aliases all state of the original: writing through either
+    /// is seen by both. See [Component::deep_clone] for an independent copy.
+    pub fn clone_shallow(&self) -> Component {
+        Self {
+            name: self.name,
+            signature: self.signature.clone(),
+            cells: self.cells.iter().cloned().into(),
+            groups: self.groups.iter().cloned().into(),
+            comb_groups: self.comb_groups.iter().cloned().into(),
+            continuous_assignments: self.continuous_assignments.clone(),
+            control: self.control.clone(),
+            attributes: self.attributes.clone(),
+        }
+    }
+
+    /// Copies this component into fresh allocations which share no state with
+    /// the original, so the copy can be simulated independently. Unlike
+    /// cloning an `Arc<Component>`, every cell, port, and group is duplicated
+    /// and all references between them, including port parents and guards,
+    /// point into the copy.
+    pub fn deep_clone(&self) -> Component {
+        let mut map = CloneMap::default();
+        Self {
+            name: self.name,
+            signature: map.get_cell(&self.signature),
+            cells: self.cells.iter().map(|x| map.get_cell(x)).into(),
+            groups: self.groups.iter().map(|x| map.get_group(x)).into(),
+            comb_groups: self
+                .comb_groups
+                .iter()
+                .map(|x| map.get_comb_group(x))
+                .into(),
+            continuous_assignments: Arc::new(
+                map.get_continuous_assignments(&self.continuous_assignments),
+            ),
+            control: map.get_control(&self.control),
+            attributes: self.attributes.clone(),
         }
     }
 }
 
-/// A straightforward copy of [calyx_ir::IdList] lifted to [ArcTex] insides
+/// A straightforward copy of [calyx_ir::IdList] lifted to [ArcTex] insides.
+/// Elements keep their insertion order and may also be looked up by their
+/// position in that order.
 #[derive(Debug)]
-pub struct IdListArcTex<T: GetName>(LinkedHashMap<Id, ArcTex<T>>);
+pub struct IdListArcTex<T: GetName> {
+    items: Vec<ArcTex<T>>,
+    indices: HashMap<Id, usize>,
+}
 
 impl<T: GetName> IdListArcTex<T> {
     /// Returns the element indicated by the name, if present, otherwise None.
@@ -78,12 +449,38 @@ impl<T: GetName> IdListArcTex<T> {
     where
         S: Into<Id>,
     {
-        self.0.get(&name.into()).map(Arc::clone)
+        self.indices
+            .get(&name.into())
+            .map(|idx| Arc::clone(&self.items[*idx]))
+    }
+
+    /// Returns the element at position `idx` in insertion order, if present.
+    pub fn get_index(&self, idx: usize) -> Option<&ArcTex<T>> {
+        self.items.get(idx)
     }
 
     /// Returns an iterator over immutable references
     pub fn iter(&self) -> impl Clone + Iterator<Item = &ArcTex<T>> {
-        self.0.values()
+        self.items.iter()
+    }
+
+    /// Applies `f` to every element. The write locks are taken one at a time
+    /// in insertion order, and each is released before the next is taken,
+    /// so at most one element is locked at any point. `f` must not lock the
+    /// element it is given, and callers holding locks on other elements
+    /// should take them in insertion order as well.
+    pub fn write_all<F: FnMut(&mut T)>(&self, mut f: F) {
+        for item in &self.items {
+            f(&mut item.write())
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.items.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.items.is_empty()
     }
 }
 
@@ -93,13 +490,45 @@ where
     F: IntoIterator<Item = ArcTex<T>>,
 {
     fn from(list: F) -> Self {
-        IdListArcTex(
-            list.into_iter()
-                .map(|item| {
-                    let name = item.read().name();
-                    (name, item)
-                })
-                .collect::<LinkedHashMap<Id, ArcTex<T>>>(),
-        )
+        let mut out = IdListArcTex {
+            items: vec![],
+            indices: HashMap::new(),
+        };
+        for item in list {
+            let name = item.read().name();
+            // as with a linked map, a repeated name replaces the earlier
+            // element and moves it to the end
+            if let Some(old) = out.indices.remove(&name) {
+                out.items.remove(old);
+                for idx in out.indices.values_mut() {
+                    if *idx > old {
+                        *idx -= 1;
+                    }
+                }
+            }
+            out.indices.insert(name, out.items.len());
+            out.items.push(item);
+        }
+        out
+    }
+}
+
+/// Validates the `ref_cells` of each invoke visited, keeping the first error
+struct RefCellChecker<'a> {
+    ctx: &'a [Arc<Component>],
+    result: InterpreterResult<()>,
+}
+
+impl ControlVisitor for RefCellChecker<'_> {
+    fn visit_invoke(&mut self, invoke: &Invoke) {
+        if self.result.is_err() || invoke.ref_cells.is_empty() {
+            return;
+        }
+        let CellType::Component { name } = invoke.comp.read().prototype else {
+            return;
+        };
+        if let Some(callee) = self.ctx.iter().find(|c| c.name == name) {
+            self.result = invoke.check_ref_cells(callee);
+        }
     }
 }
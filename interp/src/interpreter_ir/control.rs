@@ -1,19 +1,30 @@
-use calyx_ir::{self as orig_ir, Attributes, Control as CalyxControl};
-use calyx_utils::Id;
+use calyx_ir::{
+    self as orig_ir, Attributes, Control as CalyxControl, Direction,
+};
+use calyx_utils::{GetName, Id};
 
+use std::collections::HashSet;
 use std::sync::Arc;
 
 // These IR constructs are unchanged but are here re-exported for consistency
 pub use calyx_ir::Empty;
 
+use crate::errors::{InterpreterError, InterpreterResult};
 use crate::utils::ArcTex;
 
-use super::{translator::TranslationMap, Cell, CombGroup, Group, Port};
+use super::{
+    printer::{write_control, CalyxStyle, TreeStyle},
+    to_ir::IrResolver,
+    translator::TranslationMap,
+    Cell, CombGroup, Component, Group, Port, StaticGroup,
+};
 
 /// Data for the `enable` control statement.
 #[derive(Debug)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize))]
 pub struct Enable {
     /// List of components to run.
+    #[cfg_attr(feature = "serialize", serde(serialize_with = "ser::name"))]
     pub group: ArcTex<Group>,
     /// Attributes attached to this control statement.
     pub attributes: Attributes,
@@ -26,13 +37,14 @@ impl Enable {
     ) -> Self {
         Self {
             group: translator.get_group(&original.group),
-            attributes: original.attributes.clone(),
+            attributes: translator.translate_attributes(&original.attributes),
         }
     }
 }
 
 /// Data for the `seq` control statement.
 #[derive(Debug)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize))]
 pub struct Seq {
     /// List of `Control` statements to run in sequence.
     pub stmts: Vec<Control>,
@@ -51,13 +63,14 @@ impl Seq {
                 .iter()
                 .map(|x| Control::from_ir(x, translator))
                 .collect(),
-            attributes: original.attributes.clone(),
+            attributes: translator.translate_attributes(&original.attributes),
         }
     }
 }
 
 /// Data for the `par` control statement.
 #[derive(Debug)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize))]
 pub struct Par {
     /// List of `Control` statements to run in parallel.
     pub stmts: Vec<Control>,
@@ -76,17 +89,30 @@ impl Par {
                 .iter()
                 .map(|x| Control::from_ir(x, translator))
                 .collect(),
-            attributes: original.attributes.clone(),
+            attributes: translator.translate_attributes(&original.attributes),
         }
     }
 }
 
+/// How the condition of an `if` or `while` is produced.
+#[derive(Debug, Clone, Copy)]
+pub enum ConditionKind<'a> {
+    /// A combinational group drives the condition port. The group must be
+    /// settled before the port is read.
+    CombGroup(&'a ArcTex<CombGroup>),
+    /// The condition port is read directly.
+    Port(&'a ArcTex<Port>),
+}
+
 /// Data for the `if` control statement.
 #[derive(Debug)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize))]
 pub struct If {
     /// Port that connects the conditional check.
+    #[cfg_attr(feature = "serialize", serde(serialize_with = "ser::port"))]
     pub port: ArcTex<Port>,
     /// Optional combinational group attached using `with`.
+    #[cfg_attr(feature = "serialize", serde(serialize_with = "ser::opt_name"))]
     pub cond: Option<ArcTex<CombGroup>>,
     /// Control for the true branch.
     pub tbranch: Control,
@@ -109,17 +135,33 @@ impl If {
                 .map(|x| translator.get_comb_group(&x)),
             tbranch: Control::from_ir(&original.tbranch, translator),
             fbranch: Control::from_ir(&original.fbranch, translator),
-            attributes: original.attributes.clone(),
+            attributes: translator.translate_attributes(&original.attributes),
+        }
+    }
+
+    /// Returns true if the condition is driven by a combinational group.
+    pub fn uses_comb_group(&self) -> bool {
+        self.cond.is_some()
+    }
+
+    /// Returns how the condition of this `if` is produced.
+    pub fn condition_kind(&self) -> ConditionKind<'_> {
+        match &self.cond {
+            Some(cond) => ConditionKind::CombGroup(cond),
+            None => ConditionKind::Port(&self.port),
         }
     }
 }
 
 /// Data for the `if` control statement.
 #[derive(Debug)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize))]
 pub struct While {
     /// Port that connects the conditional check.
+    #[cfg_attr(feature = "serialize", serde(serialize_with = "ser::port"))]
     pub port: ArcTex<Port>,
     /// Group that makes the signal on the conditional port valid.
+    #[cfg_attr(feature = "serialize", serde(serialize_with = "ser::opt_name"))]
     pub cond: Option<ArcTex<CombGroup>>,
     /// Control for the loop body.
     pub body: Control,
@@ -139,12 +181,50 @@ impl While {
                 .as_ref()
                 .map(|x| translator.get_comb_group(&x)),
             body: Control::from_ir(&original.body, translator),
-            attributes: original.attributes.clone(),
+            attributes: translator.translate_attributes(&original.attributes),
+        }
+    }
+
+    /// Returns true if the condition is driven by a combinational group.
+    pub fn uses_comb_group(&self) -> bool {
+        self.cond.is_some()
+    }
+
+    /// Returns how the condition of this `while` is produced.
+    pub fn condition_kind(&self) -> ConditionKind<'_> {
+        match &self.cond {
+            Some(cond) => ConditionKind::CombGroup(cond),
+            None => ConditionKind::Port(&self.port),
+        }
+    }
+}
+
+/// Data for the `repeat` control statement.
+#[derive(Debug)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize))]
+pub struct Repeat {
+    /// Control for the loop body.
+    pub body: Control,
+    /// Number of times the body is run.
+    pub num_repeats: u64,
+    /// Attributes attached to this control statement.
+    pub attributes: Attributes,
+}
+
+impl Repeat {
+    pub(crate) fn from_ir(
+        original: &orig_ir::Repeat,
+        translator: &mut TranslationMap,
+    ) -> Self {
+        Self {
+            body: Control::from_ir(&original.body, translator),
+            num_repeats: original.num_repeats,
+            attributes: translator.translate_attributes(&original.attributes),
         }
     }
 }
 
-type PortMap = Vec<(Id, ArcTex<Port>)>;
+pub(super) type PortMap = Vec<(Id, ArcTex<Port>)>;
 type CellMap = Vec<(Id, ArcTex<Cell>)>;
 
 /// Data for an `invoke` control statement.
@@ -152,16 +232,21 @@ type CellMap = Vec<(Id, ArcTex<Cell>)>;
 #[cfg_attr(feature = "serialize", derive(serde::Serialize))]
 pub struct Invoke {
     /// Cell that is being invoked.
+    #[cfg_attr(feature = "serialize", serde(serialize_with = "ser::name"))]
     pub comp: ArcTex<Cell>,
     /// Mapping from name of input ports in `comp` to the port connected to it.
+    #[cfg_attr(feature = "serialize", serde(serialize_with = "ser::port_map"))]
     pub inputs: PortMap,
     /// Mapping from name of output ports in `comp` to the port connected to it.
+    #[cfg_attr(feature = "serialize", serde(serialize_with = "ser::port_map"))]
     pub outputs: PortMap,
     /// Attributes attached to this control statement.
     pub attributes: Attributes,
     /// Optional combinational group that is active when the invoke is active.
+    #[cfg_attr(feature = "serialize", serde(serialize_with = "ser::opt_name"))]
     pub comb_group: Option<ArcTex<CombGroup>>,
     /// Mapping from name of external cell in 'comp' to the cell connected to it.
+    #[cfg_attr(feature = "serialize", serde(serialize_with = "ser::cell_map"))]
     pub ref_cells: CellMap,
 }
 
@@ -182,7 +267,7 @@ impl Invoke {
                 .iter()
                 .map(|(id, x)| (*id, translator.get_port(x)))
                 .collect(),
-            attributes: original.attributes.clone(),
+            attributes: translator.translate_attributes(&original.attributes),
             comb_group: original
                 .comb_group
                 .as_ref()
@@ -194,10 +279,351 @@ impl Invoke {
                 .collect(),
         }
     }
+
+    /// Checks that every name in `inputs` is an input port of the invoked
+    /// cell and every name in `outputs` is an output port of it, erroring
+    /// with the first which is not. The invoked component is not available
+    /// here, so `ref_cells` is left to [Invoke::check_ref_cells].
+    pub fn validate(&self) -> InterpreterResult<()> {
+        let comp = self.comp.read();
+        let bindings = self
+            .inputs
+            .iter()
+            .map(|(name, _)| (name, Direction::Input, "input"))
+            .chain(
+                self.outputs
+                    .iter()
+                    .map(|(name, _)| (name, Direction::Output, "output")),
+            );
+        for (name, direction, expected) in bindings {
+            let matches = comp
+                .find(*name)
+                .map_or(false, |port| port.read().direction == direction);
+            if !matches {
+                return Err(InterpreterError::InvalidInvokePort {
+                    cell: comp.name(),
+                    port: *name,
+                    expected,
+                }
+                .into());
+            }
+        }
+        Ok(())
+    }
+
+    /// Checks that every entry of `ref_cells` names a `ref` cell of the
+    /// invoked component, `callee`.
+    pub fn check_ref_cells(&self, callee: &Component) -> InterpreterResult<()> {
+        for (name, _) in &self.ref_cells {
+            let is_ref = callee
+                .find_cell(*name)
+                .map_or(false, |cell| cell.read().is_reference());
+            if !is_ref {
+                return Err(InterpreterError::NotAReferenceCell {
+                    component: callee.name,
+                    cell: *name,
+                }
+                .into());
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Data for the `static enable` control statement.
+#[derive(Debug)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize))]
+pub struct StaticEnable {
+    /// The static group to run.
+    #[cfg_attr(feature = "serialize", serde(serialize_with = "ser::name"))]
+    pub group: ArcTex<StaticGroup>,
+    /// Attributes attached to this control statement.
+    pub attributes: Attributes,
+}
+
+impl StaticEnable {
+    pub(crate) fn from_ir(
+        original: &orig_ir::StaticEnable,
+        translator: &mut TranslationMap,
+    ) -> Self {
+        Self {
+            group: translator.get_static_group(&original.group),
+            attributes: translator.translate_attributes(&original.attributes),
+        }
+    }
+}
+
+/// Data for the `static seq` control statement.
+#[derive(Debug)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize))]
+pub struct StaticSeq {
+    /// List of `StaticControl` statements to run in sequence.
+    pub stmts: Vec<StaticControl>,
+    /// Attributes attached to this control statement.
+    pub attributes: Attributes,
+    /// Latency, in cycles
+    pub latency: u64,
+}
+
+impl StaticSeq {
+    pub(crate) fn from_ir(
+        original: &orig_ir::StaticSeq,
+        translator: &mut TranslationMap,
+    ) -> Self {
+        Self {
+            stmts: original
+                .stmts
+                .iter()
+                .map(|x| StaticControl::from_ir(x, translator))
+                .collect(),
+            attributes: translator.translate_attributes(&original.attributes),
+            latency: original.latency,
+        }
+    }
+}
+
+/// Data for the `static par` control statement.
+#[derive(Debug)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize))]
+pub struct StaticPar {
+    /// List of `StaticControl` statements to run in parallel.
+    pub stmts: Vec<StaticControl>,
+    /// Attributes attached to this control statement.
+    pub attributes: Attributes,
+    /// Latency, in cycles
+    pub latency: u64,
+}
+
+impl StaticPar {
+    pub(crate) fn from_ir(
+        original: &orig_ir::StaticPar,
+        translator: &mut TranslationMap,
+    ) -> Self {
+        Self {
+            stmts: original
+                .stmts
+                .iter()
+                .map(|x| StaticControl::from_ir(x, translator))
+                .collect(),
+            attributes: translator.translate_attributes(&original.attributes),
+            latency: original.latency,
+        }
+    }
+}
+
+/// Data for the `static if` control statement.
+#[derive(Debug)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize))]
+pub struct StaticIf {
+    /// Port that connects the conditional check.
+    #[cfg_attr(feature = "serialize", serde(serialize_with = "ser::port"))]
+    pub port: ArcTex<Port>,
+    /// Latency, in cycles
+    pub latency: u64,
+    /// Control for the true branch.
+    pub tbranch: StaticControl,
+    /// Control for the false branch.
+    pub fbranch: StaticControl,
+    /// Attributes attached to this control statement.
+    pub attributes: Attributes,
+}
+
+impl StaticIf {
+    pub(crate) fn from_ir(
+        original: &orig_ir::StaticIf,
+        translator: &mut TranslationMap,
+    ) -> Self {
+        Self {
+            port: translator.get_port(&original.port),
+            latency: original.latency,
+            tbranch: StaticControl::from_ir(&original.tbranch, translator),
+            fbranch: StaticControl::from_ir(&original.fbranch, translator),
+            attributes: translator.translate_attributes(&original.attributes),
+        }
+    }
+}
+
+/// Data for the `static repeat` control statement.
+#[derive(Debug)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize))]
+pub struct StaticRepeat {
+    /// Control for the loop body.
+    pub body: StaticControl,
+    /// Number of times the body is run.
+    pub num_repeats: u64,
+    /// Attributes attached to this control statement.
+    pub attributes: Attributes,
+    /// Latency, in cycles
+    pub latency: u64,
+}
+
+impl StaticRepeat {
+    pub(crate) fn from_ir(
+        original: &orig_ir::StaticRepeat,
+        translator: &mut TranslationMap,
+    ) -> Self {
+        Self {
+            body: StaticControl::from_ir(&original.body, translator),
+            num_repeats: original.num_repeats,
+            attributes: translator.translate_attributes(&original.attributes),
+            latency: original.latency,
+        }
+    }
+}
+
+/// Data for a `static invoke` control statement.
+#[derive(Debug)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize))]
+pub struct StaticInvoke {
+    /// Cell that is being invoked.
+    #[cfg_attr(feature = "serialize", serde(serialize_with = "ser::name"))]
+    pub comp: ArcTex<Cell>,
+    /// Latency, in cycles
+    pub latency: u64,
+    /// Mapping from name of input ports in `comp` to the port connected to it.
+    #[cfg_attr(feature = "serialize", serde(serialize_with = "ser::port_map"))]
+    pub inputs: PortMap,
+    /// Mapping from name of output ports in `comp` to the port connected to it.
+    #[cfg_attr(feature = "serialize", serde(serialize_with = "ser::port_map"))]
+    pub outputs: PortMap,
+    /// Attributes attached to this control statement.
+    pub attributes: Attributes,
+    /// Mapping from name of external cell in 'comp' to the cell connected to it.
+    #[cfg_attr(feature = "serialize", serde(serialize_with = "ser::cell_map"))]
+    pub ref_cells: CellMap,
+    /// Optional combinational group that is active when the invoke is active.
+    #[cfg_attr(feature = "serialize", serde(serialize_with = "ser::opt_name"))]
+    pub comb_group: Option<ArcTex<CombGroup>>,
+}
+
+impl StaticInvoke {
+    pub(crate) fn from_ir(
+        original: &orig_ir::StaticInvoke,
+        translator: &mut TranslationMap,
+    ) -> Self {
+        Self {
+            comp: translator.get_cell(&original.comp),
+            latency: original.latency,
+            inputs: original
+                .inputs
+                .iter()
+                .map(|(id, x)| (*id, translator.get_port(x)))
+                .collect(),
+            outputs: original
+                .outputs
+                .iter()
+                .map(|(id, x)| (*id, translator.get_port(x)))
+                .collect(),
+            attributes: translator.translate_attributes(&original.attributes),
+            ref_cells: original
+                .ref_cells
+                .iter()
+                .map(|(id, x)| (*id, translator.get_cell(x)))
+                .collect(),
+            comb_group: original
+                .comb_group
+                .as_ref()
+                .map(|x| translator.get_comb_group(x)),
+        }
+    }
+}
+
+/// Static control AST nodes. Every node has a latency known at compile time.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize))]
+pub enum StaticControl {
+    /// Runs the body a fixed number of times
+    Repeat(Arc<StaticRepeat>),
+    /// Runs a static group
+    Enable(Arc<StaticEnable>),
+    /// Represents parallel composition of static control statements.
+    Par(Arc<StaticPar>),
+    /// Represents sequential composition of static control statements.
+    Seq(Arc<StaticSeq>),
+    /// Static if statement, where both branches take the same time
+    If(Arc<StaticIf>),
+    /// Control statement that does nothing.
+    Empty(Arc<Empty>),
+    /// Invoke a sub-component with a known latency
+    Invoke(Arc<StaticInvoke>),
+}
+
+impl StaticControl {
+    /// The static counterpart of [Control::max_par_width]
+    pub fn max_par_width(&self) -> usize {
+        match self {
+            StaticControl::Par(p) => p
+                .stmts
+                .iter()
+                .map(StaticControl::max_par_width)
+                .sum::<usize>()
+                .max(1),
+            StaticControl::Seq(s) => s
+                .stmts
+                .iter()
+                .map(StaticControl::max_par_width)
+                .max()
+                .unwrap_or(1),
+            StaticControl::If(i) => {
+                i.tbranch.max_par_width().max(i.fbranch.max_par_width())
+            }
+            StaticControl::Repeat(r) => r.body.max_par_width(),
+            StaticControl::Enable(_)
+            | StaticControl::Empty(_)
+            | StaticControl::Invoke(_) => 1,
+        }
+    }
+
+    pub(crate) fn from_ir(
+        sc: &orig_ir::StaticControl,
+        translator: &mut TranslationMap,
+    ) -> Self {
+        match sc {
+            orig_ir::StaticControl::Repeat(r) => StaticControl::Repeat(
+                StaticRepeat::from_ir(r, translator).into(),
+            ),
+            orig_ir::StaticControl::Enable(e) => StaticControl::Enable(
+                StaticEnable::from_ir(e, translator).into(),
+            ),
+            orig_ir::StaticControl::Par(p) => {
+                StaticControl::Par(StaticPar::from_ir(p, translator).into())
+            }
+            orig_ir::StaticControl::Seq(s) => {
+                StaticControl::Seq(StaticSeq::from_ir(s, translator).into())
+            }
+            orig_ir::StaticControl::If(i) => {
+                StaticControl::If(StaticIf::from_ir(i, translator).into())
+            }
+            orig_ir::StaticControl::Empty(empty) => StaticControl::Empty(
+                Empty {
+                    attributes: translator
+                        .translate_attributes(&empty.attributes),
+                }
+                .into(),
+            ),
+            orig_ir::StaticControl::Invoke(i) => StaticControl::Invoke(
+                StaticInvoke::from_ir(i, translator).into(),
+            ),
+        }
+    }
+
+    /// The number of cycles this control statement takes to execute
+    pub fn get_latency(&self) -> u64 {
+        match self {
+            StaticControl::Enable(e) => e.group.read().get_latency(),
+            StaticControl::Repeat(r) => r.latency,
+            StaticControl::Seq(s) => s.latency,
+            StaticControl::Par(p) => p.latency,
+            StaticControl::If(i) => i.latency,
+            StaticControl::Invoke(i) => i.latency,
+            StaticControl::Empty(_) => 0,
+        }
+    }
 }
 
 /// Control AST nodes.
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize))]
 pub enum Control {
     /// Represents sequential composition of control statements.
     Seq(Arc<Seq>),
@@ -207,12 +633,16 @@ pub enum Control {
     If(Arc<If>),
     /// Standard imperative while statement
     While(Arc<While>),
+    /// Runs the body a fixed number of times
+    Repeat(Arc<Repeat>),
     /// Invoke a sub-component with the given port assignments
     Invoke(Arc<Invoke>),
     /// Runs the control for a list of subcomponents.
     Enable(Arc<Enable>),
     /// Control statement that does nothing.
     Empty(Arc<Empty>),
+    /// Control statements whose latency is known at compile time
+    Static(StaticControl),
 }
 
 impl Control {
@@ -239,13 +669,505 @@ impl Control {
             CalyxControl::Enable(enable) => {
                 Control::Enable(Enable::from_ir(enable, translator).into())
             }
-            CalyxControl::Static(_) => {
-                todo!("interpreter does not yet support static")
+            CalyxControl::Static(s) => {
+                Control::Static(StaticControl::from_ir(s, translator))
             }
-            CalyxControl::Repeat(_) => {
-                todo!("interpreter does not yet support repeat")
+            CalyxControl::Repeat(rep) => {
+                Control::Repeat(Repeat::from_ir(rep, translator).into())
             }
-            CalyxControl::Empty(empty) => Control::Empty(empty.clone().into()),
+            CalyxControl::Empty(empty) => Control::Empty(
+                Empty {
+                    attributes: translator
+                        .translate_attributes(&empty.attributes),
+                }
+                .into(),
+            ),
         }
     }
+
+    /// Converts this control program back into the Calyx IR for the component
+    /// held by `builder`. Groups, cells, and ports are matched to those of
+    /// the component by name, so the component must define every structure
+    /// the program refers to. Errors with the first one which is missing.
+    pub fn to_ir(
+        &self,
+        builder: &mut orig_ir::Builder,
+    ) -> InterpreterResult<CalyxControl> {
+        IrResolver {
+            comp: &*builder.component,
+        }
+        .control(self)
+    }
+
+    /// The number of group enables, static or otherwise, which appear in this
+    /// control program. Both branches of an `if` are counted even though only
+    /// one will run, and loop bodies are counted once.
+    pub fn count_enables(&self) -> usize {
+        let mut counter = EnableCounter(0);
+        counter.walk(self);
+        counter.0
+    }
+
+    /// The largest number of threads of control which may be active at once
+    /// while running this program. Each child of a `par` runs in its own
+    /// thread, so the width of a `par` is the sum of its children's widths
+    /// and a `par` nested inside another adds to the outer one. `seq`, `if`
+    /// and loops take the widest of their parts. A program with no `par`
+    /// has width 1.
+    pub fn max_par_width(&self) -> usize {
+        match self {
+            Control::Par(p) => p
+                .stmts
+                .iter()
+                .map(Control::max_par_width)
+                .sum::<usize>()
+                .max(1),
+            Control::Seq(s) => s
+                .stmts
+                .iter()
+                .map(Control::max_par_width)
+                .max()
+                .unwrap_or(1),
+            Control::If(i) => {
+                i.tbranch.max_par_width().max(i.fbranch.max_par_width())
+            }
+            Control::While(w) => w.body.max_par_width(),
+            Control::Repeat(r) => r.body.max_par_width(),
+            Control::Static(s) => s.max_par_width(),
+            Control::Invoke(_) | Control::Enable(_) | Control::Empty(_) => 1,
+        }
+    }
+
+    /// A rough estimate of the number of FSM states a dynamic lowering of this
+    /// control program would require. The estimate is computed as follows:
+    /// - `enable` and `invoke` need one state each
+    /// - `seq` needs the sum of its children
+    /// - `par` needs the maximum of its children
+    /// - `if` needs one state for the condition plus both branches
+    /// - `while` needs one state for the condition plus its body
+    /// - `repeat` needs one state for the counter plus its body
+    /// - `empty` needs no states
+    /// - static control is driven by a single counter and needs one state
+    pub fn state_count_estimate(&self) -> usize {
+        match self {
+            Control::Seq(s) => {
+                s.stmts.iter().map(Control::state_count_estimate).sum()
+            }
+            Control::Par(p) => p
+                .stmts
+                .iter()
+                .map(Control::state_count_estimate)
+                .max()
+                .unwrap_or_default(),
+            Control::If(i) => {
+                1 + i.tbranch.state_count_estimate()
+                    + i.fbranch.state_count_estimate()
+            }
+            Control::While(w) => 1 + w.body.state_count_estimate(),
+            Control::Repeat(r) => 1 + r.body.state_count_estimate(),
+            Control::Invoke(_) | Control::Enable(_) | Control::Static(_) => 1,
+            Control::Empty(_) => 0,
+        }
+    }
+
+    /// The number of cycles this control program takes, if that is known
+    /// before simulation. This is the case only when the program is static
+    /// control or `empty`, which takes no cycles. Any dynamic control
+    /// statement, even one wrapping only static children, gives `None`.
+    pub fn static_latency(&self) -> Option<u64> {
+        match self {
+            Control::Static(s) => Some(s.get_latency()),
+            Control::Empty(_) => Some(0),
+            Control::Seq(_)
+            | Control::Par(_)
+            | Control::If(_)
+            | Control::While(_)
+            | Control::Repeat(_)
+            | Control::Invoke(_)
+            | Control::Enable(_) => None,
+        }
+    }
+
+    /// Renders the control program as an indented outline with one node per
+    /// line, e.g.
+    /// ```text
+    /// seq
+    ///   enable g0
+    ///   par
+    ///     enable g1
+    /// ```
+    /// Group and cell names are shown along with any attributes. This is meant
+    /// as a quick structural overview rather than valid Calyx.
+    pub fn pretty_tree(&self) -> String {
+        let mut out = String::new();
+        write_control::<TreeStyle>(self, "", &mut out);
+        out
+    }
+
+    /// Renders the control program in Calyx-like syntax, starting `indent`
+    /// spaces in from the left margin:
+    /// ```text
+    /// seq {
+    ///   g0;
+    ///   if lt.out with cond {
+    ///     invoke add(left = r.out)(out = r.in);
+    ///   } else {
+    ///     empty;
+    ///   }
+    /// }
+    /// ```
+    /// Enables print the group name, conditions print the canonical name of
+    /// their port, and invokes list their input and output port mappings.
+    pub fn pretty_print(&self, indent: usize) -> String {
+        let mut out = String::new();
+        write_control::<CalyxStyle>(self, &" ".repeat(indent), &mut out);
+        out
+    }
+}
+
+/// A traversal over a [Control] tree. Each `visit_*` method is called when
+/// [ControlVisitor::walk] reaches the corresponding node. By default the
+/// compound nodes recurse into all of their children (both branches of an
+/// `if`) and the leaves do nothing, so implementors need only override the
+/// nodes they care about. An override which does not call `walk` on the
+/// children prunes the traversal at that node.
+pub trait ControlVisitor {
+    /// Dispatch on `control` to the matching `visit_*` method.
+    fn walk(&mut self, control: &Control) {
+        match control {
+            Control::Seq(s) => self.visit_seq(s),
+            Control::Par(p) => self.visit_par(p),
+            Control::If(i) => self.visit_if(i),
+            Control::While(w) => self.visit_while(w),
+            Control::Repeat(r) => self.visit_repeat(r),
+            Control::Invoke(i) => self.visit_invoke(i),
+            Control::Enable(e) => self.visit_enable(e),
+            Control::Empty(e) => self.visit_empty(e),
+            Control::Static(s) => self.visit_static(s),
+        }
+    }
+
+    fn visit_seq(&mut self, seq: &Seq) {
+        seq.stmts.iter().for_each(|stmt| self.walk(stmt))
+    }
+
+    fn visit_par(&mut self, par: &Par) {
+        par.stmts.iter().for_each(|stmt| self.walk(stmt))
+    }
+
+    fn visit_if(&mut self, if_: &If) {
+        self.walk(&if_.tbranch);
+        self.walk(&if_.fbranch);
+    }
+
+    fn visit_while(&mut self, while_: &While) {
+        self.walk(&while_.body)
+    }
+
+    fn visit_repeat(&mut self, repeat: &Repeat) {
+        self.walk(&repeat.body)
+    }
+
+    fn visit_invoke(&mut self, _invoke: &Invoke) {}
+
+    fn visit_enable(&mut self, _enable: &Enable) {}
+
+    fn visit_empty(&mut self, _empty: &Empty) {}
+
+    fn visit_static(&mut self, _control: &StaticControl) {}
+}
+
+/// Counts the enables reached by a walk. See [Control::count_enables].
+struct EnableCounter(usize);
+
+impl EnableCounter {
+    fn count_static(&mut self, control: &StaticControl) {
+        match control {
+            StaticControl::Enable(_) => self.0 += 1,
+            StaticControl::Seq(s) => {
+                s.stmts.iter().for_each(|stmt| self.count_static(stmt))
+            }
+            StaticControl::Par(p) => {
+                p.stmts.iter().for_each(|stmt| self.count_static(stmt))
+            }
+            StaticControl::If(i) => {
+                self.count_static(&i.tbranch);
+                self.count_static(&i.fbranch);
+            }
+            StaticControl::Repeat(r) => self.count_static(&r.body),
+            StaticControl::Empty(_) | StaticControl::Invoke(_) => {}
+        }
+    }
+}
+
+impl ControlVisitor for EnableCounter {
+    fn visit_enable(&mut self, _enable: &Enable) {
+        self.0 += 1
+    }
+
+    fn visit_static(&mut self, control: &StaticControl) {
+        self.count_static(control)
+    }
+}
+
+// Structural equality. Like the serializers below, control trees are compared
+// by the names of the structures they reference and the canonical names of
+// their ports, so two trees are equal when they have the same shape and refer
+// to the same names, whether or not they share any nodes. The contents of the
+// referenced groups are not compared.
+
+/// Compares attributes as sets, ignoring the order they were added in.
+fn same_attributes(a: &Attributes, b: &Attributes) -> bool {
+    let set = |attrs: &Attributes| {
+        attrs
+            .to_string_with("\n", |name, val| format!("{name}={val}"))
+            .lines()
+            .map(str::to_string)
+            .collect::<HashSet<_>>()
+    };
+    set(a) == set(b)
+}
+
+fn same_port(a: &ArcTex<Port>, b: &ArcTex<Port>) -> bool {
+    a.read().canonical() == b.read().canonical()
+}
+
+fn same_name<T: GetName>(a: &ArcTex<T>, b: &ArcTex<T>) -> bool {
+    a.read().name() == b.read().name()
+}
+
+fn same_opt_name<T: GetName>(
+    a: &Option<ArcTex<T>>,
+    b: &Option<ArcTex<T>>,
+) -> bool {
+    match (a, b) {
+        (Some(a), Some(b)) => same_name(a, b),
+        (None, None) => true,
+        _ => false,
+    }
+}
+
+/// Compares the bindings of an invoke, ignoring the order they were given in
+fn same_port_map(a: &PortMap, b: &PortMap) -> bool {
+    let set = |ports: &PortMap| {
+        ports
+            .iter()
+            .map(|(id, p)| (*id, p.read().canonical()))
+            .collect::<HashSet<_>>()
+    };
+    a.len() == b.len() && set(a) == set(b)
+}
+
+fn same_cell_map(a: &CellMap, b: &CellMap) -> bool {
+    let set = |cells: &CellMap| {
+        cells
+            .iter()
+            .map(|(id, c)| (*id, c.read().name()))
+            .collect::<HashSet<_>>()
+    };
+    a.len() == b.len() && set(a) == set(b)
+}
+
+impl PartialEq for Enable {
+    fn eq(&self, other: &Self) -> bool {
+        same_name(&self.group, &other.group)
+            && same_attributes(&self.attributes, &other.attributes)
+    }
+}
+
+impl PartialEq for Seq {
+    fn eq(&self, other: &Self) -> bool {
+        self.stmts == other.stmts
+            && same_attributes(&self.attributes, &other.attributes)
+    }
+}
+
+impl PartialEq for Par {
+    fn eq(&self, other: &Self) -> bool {
+        self.stmts == other.stmts
+            && same_attributes(&self.attributes, &other.attributes)
+    }
+}
+
+impl PartialEq for If {
+    fn eq(&self, other: &Self) -> bool {
+        same_port(&self.port, &other.port)
+            && same_opt_name(&self.cond, &other.cond)
+            && self.tbranch == other.tbranch
+            && self.fbranch == other.fbranch
+            && same_attributes(&self.attributes, &other.attributes)
+    }
+}
+
+impl PartialEq for While {
+    fn eq(&self, other: &Self) -> bool {
+        same_port(&self.port, &other.port)
+            && same_opt_name(&self.cond, &other.cond)
+            && self.body == other.body
+            && same_attributes(&self.attributes, &other.attributes)
+    }
+}
+
+impl PartialEq for Repeat {
+    fn eq(&self, other: &Self) -> bool {
+        self.num_repeats == other.num_repeats
+            && self.body == other.body
+            && same_attributes(&self.attributes, &other.attributes)
+    }
+}
+
+impl PartialEq for Invoke {
+    fn eq(&self, other: &Self) -> bool {
+        same_name(&self.comp, &other.comp)
+            && same_port_map(&self.inputs, &other.inputs)
+            && same_port_map(&self.outputs, &other.outputs)
+            && same_opt_name(&self.comb_group, &other.comb_group)
+            && same_cell_map(&self.ref_cells, &other.ref_cells)
+            && same_attributes(&self.attributes, &other.attributes)
+    }
+}
+
+impl PartialEq for Control {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Control::Seq(a), Control::Seq(b)) => a == b,
+            (Control::Par(a), Control::Par(b)) => a == b,
+            (Control::If(a), Control::If(b)) => a == b,
+            (Control::While(a), Control::While(b)) => a == b,
+            (Control::Repeat(a), Control::Repeat(b)) => a == b,
+            (Control::Invoke(a), Control::Invoke(b)) => a == b,
+            (Control::Enable(a), Control::Enable(b)) => a == b,
+            (Control::Empty(a), Control::Empty(b)) => {
+                same_attributes(&a.attributes, &b.attributes)
+            }
+            (Control::Static(a), Control::Static(b)) => a == b,
+            _ => false,
+        }
+    }
+}
+
+impl PartialEq for StaticEnable {
+    fn eq(&self, other: &Self) -> bool {
+        same_name(&self.group, &other.group)
+            && same_attributes(&self.attributes, &other.attributes)
+    }
+}
+
+impl PartialEq for StaticSeq {
+    fn eq(&self, other: &Self) -> bool {
+        self.latency == other.latency
+            && self.stmts == other.stmts
+            && same_attributes(&self.attributes, &other.attributes)
+    }
+}
+
+impl PartialEq for StaticPar {
+    fn eq(&self, other: &Self) -> bool {
+        self.latency == other.latency
+            && self.stmts == other.stmts
+            && same_attributes(&self.attributes, &other.attributes)
+    }
+}
+
+impl PartialEq for StaticIf {
+    fn eq(&self, other: &Self) -> bool {
+        self.latency == other.latency
+            && same_port(&self.port, &other.port)
+            && self.tbranch == other.tbranch
+            && self.fbranch == other.fbranch
+            && same_attributes(&self.attributes, &other.attributes)
+    }
+}
+
+impl PartialEq for StaticRepeat {
+    fn eq(&self, other: &Self) -> bool {
+        self.latency == other.latency
+            && self.num_repeats == other.num_repeats
+            && self.body == other.body
+            && same_attributes(&self.attributes, &other.attributes)
+    }
+}
+
+impl PartialEq for StaticInvoke {
+    fn eq(&self, other: &Self) -> bool {
+        self.latency == other.latency
+            && same_name(&self.comp, &other.comp)
+            && same_port_map(&self.inputs, &other.inputs)
+            && same_port_map(&self.outputs, &other.outputs)
+            && same_opt_name(&self.comb_group, &other.comb_group)
+            && same_cell_map(&self.ref_cells, &other.ref_cells)
+            && same_attributes(&self.attributes, &other.attributes)
+    }
+}
+
+impl PartialEq for StaticControl {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (StaticControl::Repeat(a), StaticControl::Repeat(b)) => a == b,
+            (StaticControl::Enable(a), StaticControl::Enable(b)) => a == b,
+            (StaticControl::Par(a), StaticControl::Par(b)) => a == b,
+            (StaticControl::Seq(a), StaticControl::Seq(b)) => a == b,
+            (StaticControl::If(a), StaticControl::If(b)) => a == b,
+            (StaticControl::Empty(a), StaticControl::Empty(b)) => {
+                same_attributes(&a.attributes, &b.attributes)
+            }
+            (StaticControl::Invoke(a), StaticControl::Invoke(b)) => a == b,
+            _ => false,
+        }
+    }
+}
+
+/// Serializers which record the structures referenced by the control tree by
+/// name, and ports by their canonical name, rather than following the
+/// pointers into the rest of the program.
+#[cfg(feature = "serialize")]
+mod ser {
+    use super::{CellMap, PortMap};
+    use crate::interpreter_ir::Port;
+    use crate::utils::ArcTex;
+    use calyx_utils::GetName;
+    use serde::{Serialize, Serializer};
+
+    pub fn port<S: Serializer>(
+        port: &ArcTex<Port>,
+        ser: S,
+    ) -> Result<S::Ok, S::Error> {
+        port.read().canonical().to_string().serialize(ser)
+    }
+
+    pub fn name<S: Serializer, T: GetName>(
+        target: &ArcTex<T>,
+        ser: S,
+    ) -> Result<S::Ok, S::Error> {
+        target.read().name().serialize(ser)
+    }
+
+    pub fn opt_name<S: Serializer, T: GetName>(
+        target: &Option<ArcTex<T>>,
+        ser: S,
+    ) -> Result<S::Ok, S::Error> {
+        target.as_ref().map(|t| t.read().name()).serialize(ser)
+    }
+
+    pub fn port_map<S: Serializer>(
+        ports: &PortMap,
+        ser: S,
+    ) -> Result<S::Ok, S::Error> {
+        ports
+            .iter()
+            .map(|(id, p)| (*id, p.read().canonical().to_string()))
+            .collect::<Vec<_>>()
+            .serialize(ser)
+    }
+
+    pub fn cell_map<S: Serializer>(
+        cells: &CellMap,
+        ser: S,
+    ) -> Result<S::Ok, S::Error> {
+        cells
+            .iter()
+            .map(|(id, c)| (*id, c.read().name()))
+            .collect::<Vec<_>>()
+            .serialize(ser)
+    }
 }
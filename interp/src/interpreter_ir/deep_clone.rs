@@ -0,0 +1,380 @@
+use std::any::Any;
+use std::sync::Arc;
+
+use ahash::HashMap;
+use calyx_ir::Nothing;
+use calyx_utils::Id;
+use itertools::Itertools;
+
+use crate::utils::{arctex, ArcTex, AsRaw, WeakArcTex};
+
+use super::control::*;
+use super::structure::*;
+
+/// Copies interpreter IR structures into fresh [ArcTex] allocations. This is
+/// the counterpart of the [super::TranslationMap] for structures which have
+/// already been translated: each cell, port, and group is copied exactly once
+/// and every later reference to it, including the weak parent pointers of
+/// ports and the ports read by guards, is pointed at the copy.
+#[derive(Debug, Default)]
+pub(super) struct CloneMap {
+    cell_map: HashMap<*const Cell, ArcTex<Cell>>,
+    port_map: HashMap<*const Port, ArcTex<Port>>,
+    group_map: HashMap<*const Group, ArcTex<Group>>,
+    comb_group_map: HashMap<*const CombGroup, ArcTex<CombGroup>>,
+    static_group_map: HashMap<*const StaticGroup, ArcTex<StaticGroup>>,
+    /// Copied guards keyed by the address of the original, so guards shared
+    /// by several assignments remain shared. The values are `Arc<Guard<T>>`
+    guard_map: HashMap<*const (), Box<dyn Any>>,
+}
+
+impl CloneMap {
+    pub fn get_port(&mut self, target: &ArcTex<Port>) -> ArcTex<Port> {
+        let key = target.as_raw();
+        if let Some(x) = self.port_map.get(&key) {
+            return x.clone();
+        }
+
+        let orig = target.read().clone();
+        // a parent which has already been dropped is left dangling
+        let parent: PortParent = match &orig.parent {
+            PortParent::Cell(c) => match c.try_upgrade() {
+                Some(c) => WeakArcTex::from(self.get_cell(&c)).into(),
+                None => orig.parent.clone(),
+            },
+            PortParent::Group(g) => match g.try_upgrade() {
+                Some(g) => WeakArcTex::from(self.get_group(&g)).into(),
+                None => orig.parent.clone(),
+            },
+            PortParent::StaticGroup(sg) => match sg.try_upgrade() {
+                Some(sg) => WeakArcTex::from(self.get_static_group(&sg)).into(),
+                None => orig.parent.clone(),
+            },
+        };
+
+        // copying the parent copies its ports, this one included
+        if let Some(x) = self.port_map.get(&key) {
+            return x.clone();
+        }
+        let v = arctex(Port {
+            name: orig.name,
+            width: orig.width,
+            direction: orig.direction.clone(),
+            parent,
+            attributes: orig.attributes.clone(),
+            uid: orig.uid,
+        });
+        self.port_map.insert(key, v.clone());
+        v
+    }
+
+    pub fn get_cell(&mut self, target: &ArcTex<Cell>) -> ArcTex<Cell> {
+        let key = target.as_raw();
+        if let Some(x) = self.cell_map.get(&key) {
+            return x.clone();
+        }
+
+        let v = arctex(target.read().clone_partial());
+        self.cell_map.insert(key, v.clone());
+        let ports = target.read().ports.clone();
+        let ports = ports.iter().map(|x| self.get_port(x)).collect_vec();
+        v.write().ports.extend(ports);
+        v
+    }
+
+    pub fn get_group(&mut self, target: &ArcTex<Group>) -> ArcTex<Group> {
+        let key = target.as_raw();
+        if let Some(x) = self.group_map.get(&key) {
+            return x.clone();
+        }
+
+        let v = arctex(target.read().clone_partial());
+        self.group_map.insert(key, v.clone());
+        let (holes, assigns) = {
+            let orig = target.read();
+            (orig.holes.clone(), orig.assignments.clone())
+        };
+        let holes = holes.iter().map(|x| self.get_port(x)).collect_vec();
+        let assigns =
+            assigns.iter().map(|x| self.get_assignment(x)).collect_vec();
+        let mut new = v.write();
+        new.holes.extend(holes);
+        new.assignments.extend(assigns);
+        drop(new);
+        v
+    }
+
+    pub fn get_static_group(
+        &mut self,
+        target: &ArcTex<StaticGroup>,
+    ) -> ArcTex<StaticGroup> {
+        let key = target.as_raw();
+        if let Some(x) = self.static_group_map.get(&key) {
+            return x.clone();
+        }
+
+        let v = arctex(target.read().clone_partial());
+        self.static_group_map.insert(key, v.clone());
+        let (holes, assigns) = {
+            let orig = target.read();
+            (orig.holes.clone(), orig.assignments.clone())
+        };
+        let holes = holes.iter().map(|x| self.get_port(x)).collect_vec();
+        let assigns =
+            assigns.iter().map(|x| self.get_assignment(x)).collect_vec();
+        let mut new = v.write();
+        new.holes.extend(holes);
+        new.assignments.extend(assigns);
+        drop(new);
+        v
+    }
+
+    pub fn get_comb_group(
+        &mut self,
+        target: &ArcTex<CombGroup>,
+    ) -> ArcTex<CombGroup> {
+        let key = target.as_raw();
+        if let Some(x) = self.comb_group_map.get(&key) {
+            return x.clone();
+        }
+
+        let orig = target.read();
+        let v = arctex(CombGroup {
+            name: orig.name,
+            assignments: orig
+                .assignments
+                .iter()
+                .map(|x| self.get_assignment(x))
+                .collect(),
+            attributes: orig.attributes.clone(),
+        });
+        self.comb_group_map.insert(key, v.clone());
+        v
+    }
+
+    pub fn get_assignment<T: Clone + 'static>(
+        &mut self,
+        target: &Assignment<T>,
+    ) -> Assignment<T> {
+        Assignment {
+            dst: self.get_port(&target.dst),
+            src: self.get_port(&target.src),
+            guard: self.get_guard(&target.guard),
+            attributes: target.attributes.clone(),
+        }
+    }
+
+    fn get_guard<T: Clone + 'static>(
+        &mut self,
+        target: &Arc<Guard<T>>,
+    ) -> Arc<Guard<T>> {
+        let key = Arc::as_ptr(target) as *const ();
+        if let Some(existing) = self
+            .guard_map
+            .get(&key)
+            .and_then(|x| x.downcast_ref::<Arc<Guard<T>>>())
+        {
+            return existing.clone();
+        }
+
+        let guard = Arc::new(self.copy_guard(target));
+        self.guard_map.insert(key, Box::new(guard.clone()));
+        guard
+    }
+
+    fn copy_guard<T: Clone>(&mut self, guard: &Guard<T>) -> Guard<T> {
+        match guard {
+            Guard::Or(l, r) => {
+                Guard::Or(self.copy_guard(l).into(), self.copy_guard(r).into())
+            }
+            Guard::And(l, r) => {
+                Guard::And(self.copy_guard(l).into(), self.copy_guard(r).into())
+            }
+            Guard::Not(g) => Guard::Not(self.copy_guard(g).into()),
+            Guard::True => Guard::True,
+            Guard::False => Guard::False,
+            Guard::CompOp(op, l, r) => {
+                Guard::CompOp(op.clone(), self.get_port(l), self.get_port(r))
+            }
+            Guard::Port(p) => Guard::Port(self.get_port(p)),
+            Guard::Info(i) => Guard::Info(i.clone()),
+        }
+    }
+
+    pub fn get_continuous_assignments(
+        &mut self,
+        target: &[Assignment<Nothing>],
+    ) -> Vec<Assignment<Nothing>> {
+        target.iter().map(|x| self.get_assignment(x)).collect()
+    }
+
+    pub fn get_control(&mut self, control: &Control) -> Control {
+        match control {
+            Control::Seq(s) => Control::Seq(
+                Seq {
+                    stmts: s
+                        .stmts
+                        .iter()
+                        .map(|x| self.get_control(x))
+                        .collect(),
+                    attributes: s.attributes.clone(),
+                }
+                .into(),
+            ),
+            Control::Par(p) => Control::Par(
+                Par {
+                    stmts: p
+                        .stmts
+                        .iter()
+                        .map(|x| self.get_control(x))
+                        .collect(),
+                    attributes: p.attributes.clone(),
+                }
+                .into(),
+            ),
+            Control::If(i) => Control::If(
+                If {
+                    port: self.get_port(&i.port),
+                    cond: i.cond.as_ref().map(|x| self.get_comb_group(x)),
+                    tbranch: self.get_control(&i.tbranch),
+                    fbranch: self.get_control(&i.fbranch),
+                    attributes: i.attributes.clone(),
+                }
+                .into(),
+            ),
+            Control::While(w) => Control::While(
+                While {
+                    port: self.get_port(&w.port),
+                    cond: w.cond.as_ref().map(|x| self.get_comb_group(x)),
+                    body: self.get_control(&w.body),
+                    attributes: w.attributes.clone(),
+                }
+                .into(),
+            ),
+            Control::Repeat(r) => Control::Repeat(
+                Repeat {
+                    body: self.get_control(&r.body),
+                    num_repeats: r.num_repeats,
+                    attributes: r.attributes.clone(),
+                }
+                .into(),
+            ),
+            Control::Invoke(i) => Control::Invoke(
+                Invoke {
+                    comp: self.get_cell(&i.comp),
+                    inputs: self.get_port_map(&i.inputs),
+                    outputs: self.get_port_map(&i.outputs),
+                    attributes: i.attributes.clone(),
+                    comb_group: i
+                        .comb_group
+                        .as_ref()
+                        .map(|x| self.get_comb_group(x)),
+                    ref_cells: self.get_cell_map(&i.ref_cells),
+                }
+                .into(),
+            ),
+            Control::Enable(e) => Control::Enable(
+                Enable {
+                    group: self.get_group(&e.group),
+                    attributes: e.attributes.clone(),
+                }
+                .into(),
+            ),
+            Control::Empty(e) => Control::Empty(Arc::new(Empty::clone(e))),
+            Control::Static(s) => Control::Static(self.get_static_control(s)),
+        }
+    }
+
+    fn get_static_control(&mut self, control: &StaticControl) -> StaticControl {
+        match control {
+            StaticControl::Repeat(r) => StaticControl::Repeat(
+                StaticRepeat {
+                    body: self.get_static_control(&r.body),
+                    num_repeats: r.num_repeats,
+                    attributes: r.attributes.clone(),
+                    latency: r.latency,
+                }
+                .into(),
+            ),
+            StaticControl::Enable(e) => StaticControl::Enable(
+                StaticEnable {
+                    group: self.get_static_group(&e.group),
+                    attributes: e.attributes.clone(),
+                }
+                .into(),
+            ),
+            StaticControl::Par(p) => StaticControl::Par(
+                StaticPar {
+                    stmts: p
+                        .stmts
+                        .iter()
+                        .map(|x| self.get_static_control(x))
+                        .collect(),
+                    attributes: p.attributes.clone(),
+                    latency: p.latency,
+                }
+                .into(),
+            ),
+            StaticControl::Seq(s) => StaticControl::Seq(
+                StaticSeq {
+                    stmts: s
+                        .stmts
+                        .iter()
+                        .map(|x| self.get_static_control(x))
+                        .collect(),
+                    attributes: s.attributes.clone(),
+                    latency: s.latency,
+                }
+                .into(),
+            ),
+            StaticControl::If(i) => StaticControl::If(
+                StaticIf {
+                    port: self.get_port(&i.port),
+                    latency: i.latency,
+                    tbranch: self.get_static_control(&i.tbranch),
+                    fbranch: self.get_static_control(&i.fbranch),
+                    attributes: i.attributes.clone(),
+                }
+                .into(),
+            ),
+            StaticControl::Empty(e) => {
+                StaticControl::Empty(Arc::new(Empty::clone(e)))
+            }
+            StaticControl::Invoke(i) => StaticControl::Invoke(
+                StaticInvoke {
+                    comp: self.get_cell(&i.comp),
+                    latency: i.latency,
+                    inputs: self.get_port_map(&i.inputs),
+                    outputs: self.get_port_map(&i.outputs),
+                    attributes: i.attributes.clone(),
+                    ref_cells: self.get_cell_map(&i.ref_cells),
+                    comb_group: i
+                        .comb_group
+                        .as_ref()
+                        .map(|x| self.get_comb_group(x)),
+                }
+                .into(),
+            ),
+        }
+    }
+
+    fn get_port_map(
+        &mut self,
+        ports: &[(Id, ArcTex<Port>)],
+    ) -> Vec<(Id, ArcTex<Port>)> {
+        ports
+            .iter()
+            .map(|(id, x)| (*id, self.get_port(x)))
+            .collect()
+    }
+
+    fn get_cell_map(
+        &mut self,
+        cells: &[(Id, ArcTex<Cell>)],
+    ) -> Vec<(Id, ArcTex<Cell>)> {
+        cells
+            .iter()
+            .map(|(id, x)| (*id, self.get_cell(x)))
+            .collect()
+    }
+}
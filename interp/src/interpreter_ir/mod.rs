@@ -8,18 +8,30 @@
 //! effect this enables (relatively) cheap cloning for control structures as they are
 //! only needed in a read-only capacity.
 
+mod builder;
+mod comb_graph;
 mod component;
 mod control;
-
+mod deep_clone;
+mod printer;
+mod structural_hash;
 mod structure;
+mod to_ir;
 mod translator;
+mod validation;
 
+pub use builder::ComponentBuilder;
 pub use component::Component;
-pub use control::{Control, Empty, Enable, If, Invoke, Par, Seq, While};
+pub use control::{
+    ConditionKind, Control, ControlVisitor, Empty, Enable, If, Invoke, Par,
+    Repeat, Seq, StaticControl, StaticEnable, StaticIf, StaticInvoke,
+    StaticPar, StaticRepeat, StaticSeq, While,
+};
 // TODO: De-glob this when ready
 pub use structure::*;
 
 use std::sync::Arc;
 pub type ComponentCtx = Arc<Vec<Arc<component::Component>>>;
 pub type ContinuousAssignments = Arc<Vec<Assignment<calyx_ir::Nothing>>>;
-pub use translator::TranslationMap;
+pub use translator::{TranslationMap, TranslationStats};
+pub use validation::ValidationWarning;
@@ -0,0 +1,306 @@
+use std::fmt::Write;
+
+use calyx_ir::Attributes;
+use itertools::Itertools;
+
+use crate::utils::ArcTex;
+
+use super::control::*;
+use super::CombGroup;
+
+/// How each statement is rendered by [write_control]. Every statement takes
+/// one line, and the children of compound statements follow it indented by
+/// two more spaces. Styles differ only in these lines and in whether compound
+/// statements are closed.
+pub(super) trait ControlStyle {
+    /// The line separating the branches of an `if`
+    const ELSE: &'static str;
+    /// The line closing a compound statement, if there is one
+    const CLOSE: Option<&'static str>;
+
+    fn line(control: &Control) -> String;
+
+    fn static_line(control: &StaticControl) -> String;
+}
+
+/// Valid Calyx syntax. See [Control::pretty_print]
+pub(super) struct CalyxStyle;
+
+/// An outline of the nodes. See [Control::pretty_tree]
+pub(super) struct TreeStyle;
+
+impl ControlStyle for CalyxStyle {
+    const ELSE: &'static str = "} else {";
+    const CLOSE: Option<&'static str> = Some("}");
+
+    fn line(control: &Control) -> String {
+        match control {
+            Control::Seq(s) => format!("{}seq {{", attr_prefix(&s.attributes)),
+            Control::Par(p) => format!("{}par {{", attr_prefix(&p.attributes)),
+            Control::If(i) => format!(
+                "{}if {}{} {{",
+                attr_prefix(&i.attributes),
+                i.port.read().canonical(),
+                fmt_cond(&i.cond),
+            ),
+            Control::While(w) => format!(
+                "{}while {}{} {{",
+                attr_prefix(&w.attributes),
+                w.port.read().canonical(),
+                fmt_cond(&w.cond),
+            ),
+            Control::Repeat(r) => format!(
+                "{}repeat {} {{",
+                attr_prefix(&r.attributes),
+                r.num_repeats
+            ),
+            Control::Invoke(i) => format!(
+                "{}invoke {}{}{}{};",
+                attr_prefix(&i.attributes),
+                i.comp.read().name(),
+                fmt_port_map(&i.inputs),
+                fmt_port_map(&i.outputs),
+                fmt_cond(&i.comb_group),
+            ),
+            Control::Enable(e) => format!(
+                "{}{};",
+                attr_prefix(&e.attributes),
+                e.group.read().name()
+            ),
+            Control::Empty(e) => {
+                format!("{}empty;", attr_prefix(&e.attributes))
+            }
+            Control::Static(s) => Self::static_line(s),
+        }
+    }
+
+    fn static_line(control: &StaticControl) -> String {
+        let latency = control.get_latency();
+        match control {
+            StaticControl::Repeat(r) => format!(
+                "{}static repeat {} {{",
+                attr_prefix(&r.attributes),
+                r.num_repeats
+            ),
+            StaticControl::Enable(e) => format!(
+                "{}{};",
+                attr_prefix(&e.attributes),
+                e.group.read().name()
+            ),
+            StaticControl::Par(p) => format!(
+                "{}static<{latency}> par {{",
+                attr_prefix(&p.attributes)
+            ),
+            StaticControl::Seq(s) => format!(
+                "{}static<{latency}> seq {{",
+                attr_prefix(&s.attributes)
+            ),
+            StaticControl::If(i) => format!(
+                "{}static<{latency}> if {} {{",
+                attr_prefix(&i.attributes),
+                i.port.read().canonical(),
+            ),
+            StaticControl::Empty(e) => {
+                format!("{}empty;", attr_prefix(&e.attributes))
+            }
+            StaticControl::Invoke(i) => format!(
+                "{}static<{latency}> invoke {}{}{};",
+                attr_prefix(&i.attributes),
+                i.comp.read().name(),
+                fmt_port_map(&i.inputs),
+                fmt_port_map(&i.outputs),
+            ),
+        }
+    }
+}
+
+impl ControlStyle for TreeStyle {
+    const ELSE: &'static str = "else";
+    const CLOSE: Option<&'static str> = None;
+
+    fn line(control: &Control) -> String {
+        match control {
+            Control::Seq(s) => format!("seq{}", fmt_attrs(&s.attributes)),
+            Control::Par(p) => format!("par{}", fmt_attrs(&p.attributes)),
+            Control::If(i) => format!(
+                "if {}{}{}",
+                i.port.read().canonical(),
+                fmt_cond(&i.cond),
+                fmt_attrs(&i.attributes)
+            ),
+            Control::While(w) => format!(
+                "while {}{}{}",
+                w.port.read().canonical(),
+                fmt_cond(&w.cond),
+                fmt_attrs(&w.attributes)
+            ),
+            Control::Repeat(r) => {
+                format!("repeat {}{}", r.num_repeats, fmt_attrs(&r.attributes))
+            }
+            Control::Invoke(i) => format!(
+                "invoke {}{}",
+                i.comp.read().name(),
+                fmt_attrs(&i.attributes)
+            ),
+            Control::Enable(e) => format!(
+                "enable {}{}",
+                e.group.read().name(),
+                fmt_attrs(&e.attributes)
+            ),
+            Control::Empty(e) => format!("empty{}", fmt_attrs(&e.attributes)),
+            Control::Static(s) => Self::static_line(s),
+        }
+    }
+
+    fn static_line(control: &StaticControl) -> String {
+        let latency = control.get_latency();
+        match control {
+            StaticControl::Repeat(r) => format!(
+                "static<{latency}> repeat {}{}",
+                r.num_repeats,
+                fmt_attrs(&r.attributes)
+            ),
+            StaticControl::Enable(e) => format!(
+                "static<{latency}> enable {}{}",
+                e.group.read().name(),
+                fmt_attrs(&e.attributes)
+            ),
+            StaticControl::Par(p) => {
+                format!("static<{latency}> par{}", fmt_attrs(&p.attributes))
+            }
+            StaticControl::Seq(s) => {
+                format!("static<{latency}> seq{}", fmt_attrs(&s.attributes))
+            }
+            StaticControl::If(i) => format!(
+                "static<{latency}> if {}{}",
+                i.port.read().canonical(),
+                fmt_attrs(&i.attributes)
+            ),
+            StaticControl::Empty(e) => {
+                format!("empty{}", fmt_attrs(&e.attributes))
+            }
+            StaticControl::Invoke(i) => format!(
+                "static<{latency}> invoke {}{}",
+                i.comp.read().name(),
+                fmt_attrs(&i.attributes)
+            ),
+        }
+    }
+}
+
+/// Writes `control` in the style `S`, with each line starting with `pad`
+pub(super) fn write_control<S: ControlStyle>(
+    control: &Control,
+    pad: &str,
+    out: &mut String,
+) {
+    if let Control::Static(s) = control {
+        return write_static::<S>(s, pad, out);
+    }
+
+    writeln!(out, "{pad}{}", S::line(control)).unwrap();
+    let inner = format!("{pad}  ");
+    match control {
+        Control::Seq(s) => {
+            for stmt in s.stmts.iter() {
+                write_control::<S>(stmt, &inner, out);
+            }
+        }
+        Control::Par(p) => {
+            for stmt in p.stmts.iter() {
+                write_control::<S>(stmt, &inner, out);
+            }
+        }
+        Control::If(i) => {
+            write_control::<S>(&i.tbranch, &inner, out);
+            writeln!(out, "{pad}{}", S::ELSE).unwrap();
+            write_control::<S>(&i.fbranch, &inner, out);
+        }
+        Control::While(w) => write_control::<S>(&w.body, &inner, out),
+        Control::Repeat(r) => write_control::<S>(&r.body, &inner, out),
+        Control::Invoke(_)
+        | Control::Enable(_)
+        | Control::Empty(_)
+        | Control::Static(_) => return,
+    }
+    close::<S>(pad, out);
+}
+
+fn write_static<S: ControlStyle>(
+    control: &StaticControl,
+    pad: &str,
+    out: &mut String,
+) {
+    writeln!(out, "{pad}{}", S::static_line(control)).unwrap();
+    let inner = format!("{pad}  ");
+    match control {
+        StaticControl::Repeat(r) => write_static::<S>(&r.body, &inner, out),
+        StaticControl::Par(p) => {
+            for stmt in p.stmts.iter() {
+                write_static::<S>(stmt, &inner, out);
+            }
+        }
+        StaticControl::Seq(s) => {
+            for stmt in s.stmts.iter() {
+                write_static::<S>(stmt, &inner, out);
+            }
+        }
+        StaticControl::If(i) => {
+            write_static::<S>(&i.tbranch, &inner, out);
+            writeln!(out, "{pad}{}", S::ELSE).unwrap();
+            write_static::<S>(&i.fbranch, &inner, out);
+        }
+        StaticControl::Enable(_)
+        | StaticControl::Empty(_)
+        | StaticControl::Invoke(_) => return,
+    }
+    close::<S>(pad, out);
+}
+
+fn close<S: ControlStyle>(pad: &str, out: &mut String) {
+    if let Some(close) = S::CLOSE {
+        writeln!(out, "{pad}{close}").unwrap();
+    }
+}
+
+/// Formats the attributes as a space-prefixed list, e.g. ` @static(2)`, or
+/// returns an empty string if there are none.
+fn fmt_attrs(attrs: &Attributes) -> String {
+    if attrs.is_empty() {
+        return String::new();
+    }
+    let attrs = attrs.to_string_with(" ", |name, val| {
+        if val == 1 {
+            format!("@{name}")
+        } else {
+            format!("@{name}({val})")
+        }
+    });
+    format!(" {attrs}")
+}
+
+/// Formats the attributes as a space-separated list followed by a space, e.g.
+/// `@static(2) `, so they can be placed in front of a statement.
+fn attr_prefix(attrs: &Attributes) -> String {
+    let attrs = fmt_attrs(attrs);
+    if attrs.is_empty() {
+        attrs
+    } else {
+        format!("{} ", attrs.trim_start())
+    }
+}
+
+/// Formats an invoke port mapping as `(name = cell.port, ...)`
+fn fmt_port_map(ports: &PortMap) -> String {
+    let ports = ports
+        .iter()
+        .map(|(name, port)| format!("{name} = {}", port.read().canonical()))
+        .join(", ");
+    format!("({ports})")
+}
+
+fn fmt_cond(cond: &Option<ArcTex<CombGroup>>) -> String {
+    cond.as_ref()
+        .map(|c| format!(" with {}", c.read().name()))
+        .unwrap_or_default()
+}
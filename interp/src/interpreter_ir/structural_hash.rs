@@ -0,0 +1,256 @@
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+use std::mem::discriminant;
+
+use calyx_ir::{CellType, Direction};
+use calyx_utils::Id;
+
+use crate::utils::ArcTex;
+
+use super::control::*;
+use super::structure::*;
+use super::Component;
+
+/// Hashes the structure of a component: the prototypes and ports of its
+/// cells, the assignments of its groups, and the shape of its control.
+/// Structures are hashed by name and ports by their canonical names, and
+/// names are hashed as strings rather than interned symbols, so the result
+/// depends neither on addresses nor on the order names were interned in.
+/// Attributes are not hashed.
+pub(super) struct StructuralHasher {
+    state: DefaultHasher,
+}
+
+impl StructuralHasher {
+    pub fn hash_component(comp: &Component) -> u64 {
+        let mut hasher = Self {
+            state: DefaultHasher::new(),
+        };
+
+        hasher.id(comp.name);
+        hasher.cell(&comp.signature);
+        hasher.len(comp.cells.len());
+        for cell in comp.cells.iter() {
+            hasher.cell(cell);
+        }
+        hasher.len(comp.groups.len());
+        for group in comp.groups.iter() {
+            let group = group.read();
+            hasher.id(group.name());
+            hasher.ports(&group.holes);
+            hasher.assignments(&group.assignments);
+        }
+        hasher.len(comp.comb_groups.len());
+        for group in comp.comb_groups.iter() {
+            hasher.comb_group(group);
+        }
+        hasher.assignments(&comp.continuous_assignments);
+        hasher.control(&comp.control);
+
+        hasher.state.finish()
+    }
+
+    fn id(&mut self, id: Id) {
+        AsRef::<str>::as_ref(&id).hash(&mut self.state)
+    }
+
+    /// Hashes a length so that adjacent lists cannot run into each other
+    fn len(&mut self, len: usize) {
+        len.hash(&mut self.state)
+    }
+
+    fn port(&mut self, port: &ArcTex<Port>) {
+        let port = port.read();
+        let canonical = port.canonical();
+        self.id(canonical.0);
+        self.id(canonical.1);
+    }
+
+    fn ports(&mut self, ports: &[ArcTex<Port>]) {
+        self.len(ports.len());
+        for port in ports {
+            self.port(port);
+            let port = port.read();
+            port.width.hash(&mut self.state);
+            let direction = match port.direction {
+                Direction::Input => 0_u8,
+                Direction::Output => 1,
+                Direction::Inout => 2,
+            };
+            direction.hash(&mut self.state);
+        }
+    }
+
+    fn cell(&mut self, cell: &ArcTex<Cell>) {
+        let cell = cell.read();
+        self.id(cell.name());
+        cell.is_reference().hash(&mut self.state);
+        let prototype = &cell.prototype;
+        discriminant(prototype).hash(&mut self.state);
+        match prototype {
+            CellType::Primitive {
+                name,
+                param_binding,
+                is_comb,
+                latency,
+            } => {
+                self.id(*name);
+                self.len(param_binding.len());
+                for (param, value) in param_binding.iter() {
+                    self.id(*param);
+                    value.hash(&mut self.state);
+                }
+                is_comb.hash(&mut self.state);
+                latency.hash(&mut self.state);
+            }
+            CellType::Component { name } => self.id(*name),
+            CellType::ThisComponent => {}
+            CellType::Constant { val, width } => {
+                val.hash(&mut self.state);
+                width.hash(&mut self.state);
+            }
+        }
+        self.ports(cell.ports());
+    }
+
+    fn comb_group(&mut self, group: &ArcTex<CombGroup>) {
+        let group = group.read();
+        self.id(group.name());
+        self.assignments(&group.assignments);
+    }
+
+    fn assignments<T: ToString>(&mut self, assigns: &[Assignment<T>]) {
+        self.len(assigns.len());
+        for assign in assigns {
+            self.port(&assign.dst);
+            self.port(&assign.src);
+            self.guard(&assign.guard);
+        }
+    }
+
+    fn guard<T: ToString>(&mut self, guard: &Guard<T>) {
+        discriminant(guard).hash(&mut self.state);
+        match guard {
+            Guard::Or(l, r) | Guard::And(l, r) => {
+                self.guard(l);
+                self.guard(r);
+            }
+            Guard::Not(g) => self.guard(g),
+            Guard::True | Guard::False => {}
+            Guard::CompOp(op, l, r) => {
+                discriminant(op).hash(&mut self.state);
+                self.port(l);
+                self.port(r);
+            }
+            Guard::Port(p) => self.port(p),
+            Guard::Info(i) => i.to_string().hash(&mut self.state),
+        }
+    }
+
+    fn port_map(&mut self, ports: &[(Id, ArcTex<Port>)]) {
+        self.len(ports.len());
+        for (name, port) in ports {
+            self.id(*name);
+            self.port(port);
+        }
+    }
+
+    fn cell_map(&mut self, cells: &[(Id, ArcTex<Cell>)]) {
+        self.len(cells.len());
+        for (name, cell) in cells {
+            self.id(*name);
+            self.id(cell.read().name());
+        }
+    }
+
+    fn comb_group_name(&mut self, group: &Option<ArcTex<CombGroup>>) {
+        group.is_some().hash(&mut self.state);
+        if let Some(group) = group {
+            self.id(group.read().name());
+        }
+    }
+
+    fn control(&mut self, control: &Control) {
+        discriminant(control).hash(&mut self.state);
+        match control {
+            Control::Seq(s) => {
+                self.len(s.stmts.len());
+                s.stmts.iter().for_each(|x| self.control(x));
+            }
+            Control::Par(p) => {
+                self.len(p.stmts.len());
+                p.stmts.iter().for_each(|x| self.control(x));
+            }
+            Control::If(i) => {
+                self.port(&i.port);
+                self.comb_group_name(&i.cond);
+                self.control(&i.tbranch);
+                self.control(&i.fbranch);
+            }
+            Control::While(w) => {
+                self.port(&w.port);
+                self.comb_group_name(&w.cond);
+                self.control(&w.body);
+            }
+            Control::Repeat(r) => {
+                r.num_repeats.hash(&mut self.state);
+                self.control(&r.body);
+            }
+            Control::Invoke(i) => {
+                self.id(i.comp.read().name());
+                self.port_map(&i.inputs);
+                self.port_map(&i.outputs);
+                self.comb_group_name(&i.comb_group);
+                self.cell_map(&i.ref_cells);
+            }
+            Control::Enable(e) => self.id(e.group.read().name()),
+            Control::Empty(_) => {}
+            Control::Static(s) => self.static_control(s),
+        }
+    }
+
+    /// Static groups are only reachable through the control, so their
+    /// contents are hashed wherever they are enabled.
+    fn static_control(&mut self, control: &StaticControl) {
+        discriminant(control).hash(&mut self.state);
+        match control {
+            StaticControl::Repeat(r) => {
+                r.num_repeats.hash(&mut self.state);
+                r.latency.hash(&mut self.state);
+                self.static_control(&r.body);
+            }
+            StaticControl::Enable(e) => {
+                let group = e.group.read();
+                self.id(group.name());
+                group.latency.hash(&mut self.state);
+                self.ports(&group.holes);
+                self.assignments(&group.assignments);
+            }
+            StaticControl::Par(p) => {
+                p.latency.hash(&mut self.state);
+                self.len(p.stmts.len());
+                p.stmts.iter().for_each(|x| self.static_control(x));
+            }
+            StaticControl::Seq(s) => {
+                s.latency.hash(&mut self.state);
+                self.len(s.stmts.len());
+                s.stmts.iter().for_each(|x| self.static_control(x));
+            }
+            StaticControl::If(i) => {
+                i.latency.hash(&mut self.state);
+                self.port(&i.port);
+                self.static_control(&i.tbranch);
+                self.static_control(&i.fbranch);
+            }
+            StaticControl::Empty(_) => {}
+            StaticControl::Invoke(i) => {
+                i.latency.hash(&mut self.state);
+                self.id(i.comp.read().name());
+                self.port_map(&i.inputs);
+                self.port_map(&i.outputs);
+                self.comb_group_name(&i.comb_group);
+                self.cell_map(&i.ref_cells);
+            }
+        }
+    }
+}
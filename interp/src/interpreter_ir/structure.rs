@@ -1,22 +1,35 @@
+use std::collections::HashSet;
 use std::sync::Arc;
 
-use calyx_frontend::{Attribute, Attributes, Direction};
-use calyx_ir::{self as orig_ir, CellType, Nothing, PortComp, RRC};
+use calyx_frontend::{Attribute, Attributes, Direction, NumAttr};
+use calyx_ir::{
+    self as orig_ir, CellType, Nothing, PortComp, StaticTiming, RRC,
+};
 
 use calyx_utils::{GetName, Id};
 use itertools::Itertools;
 use orig_ir::Canonical;
 use smallvec::SmallVec;
 
+use crate::errors::InterpreterResult;
 use crate::utils::{ArcTex, WeakArcTex};
+use crate::values::Value;
 
+use super::comb_graph;
 use super::translator::TranslationMap;
 
-/// Ports can come from Cells or Groups
+/// Ports can come from Cells, Groups, or Static Groups
 #[derive(Debug, Clone)]
 pub enum PortParent {
     Cell(WeakArcTex<Cell>),
     Group(WeakArcTex<Group>),
+    StaticGroup(WeakArcTex<StaticGroup>),
+}
+
+impl From<WeakArcTex<StaticGroup>> for PortParent {
+    fn from(v: WeakArcTex<StaticGroup>) -> Self {
+        Self::StaticGroup(v)
+    }
 }
 
 impl From<WeakArcTex<Group>> for PortParent {
@@ -31,6 +44,43 @@ impl From<WeakArcTex<Cell>> for PortParent {
     }
 }
 
+impl PortParent {
+    /// Returns the name of the cell or group this parent points to.
+    pub fn name(&self) -> Id {
+        match self {
+            PortParent::Cell(cell) => cell.upgrade().read().name,
+            PortParent::Group(group) => group.upgrade().read().name,
+            PortParent::StaticGroup(group) => group.upgrade().read().name,
+        }
+    }
+
+    /// Returns the name of the cell or group this parent points to, or `None`
+    /// if it has been dropped.
+    pub fn try_name(&self) -> Option<Id> {
+        Some(match self {
+            PortParent::Cell(cell) => cell.try_upgrade()?.read().name,
+            PortParent::Group(group) => group.try_upgrade()?.read().name,
+            PortParent::StaticGroup(group) => group.try_upgrade()?.read().name,
+        })
+    }
+}
+
+/// Stands in for the name of a port's parent once the parent has been dropped
+const DROPPED_PARENT: &str = "<dropped>";
+
+/// The parent is serialized as its name rather than by following the weak
+/// pointer, which would otherwise recurse back into the port.
+#[cfg(feature = "serialize")]
+impl serde::Serialize for PortParent {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        let name = self.try_name().unwrap_or_else(|| Id::from(DROPPED_PARENT));
+        serde::Serialize::serialize(&name, serializer)
+    }
+}
+
 /// Represents a port on a cell.
 #[derive(Debug, Clone)]
 #[cfg_attr(feature = "serialize", derive(serde::Serialize))]
@@ -45,6 +95,8 @@ pub struct Port {
     pub parent: PortParent,
     /// Attributes associated with this port.
     pub attributes: Attributes,
+    /// Identifier assigned in translation order, see [Port::uid]
+    pub(crate) uid: u32,
 }
 
 impl Port {
@@ -64,9 +116,12 @@ impl Port {
                 let group_ref = WeakArcTex::from(translator.get_group(&g));
                 group_ref.into()
             }
-            orig_ir::PortParent::StaticGroup(_) => unimplemented!(
-                "interpreter does not currently support static groups"
-            ),
+            orig_ir::PortParent::StaticGroup(sg) => {
+                let sg = sg.upgrade();
+                let group_ref =
+                    WeakArcTex::from(translator.get_static_group(&sg));
+                group_ref.into()
+            }
         };
 
         Self {
@@ -74,23 +129,69 @@ impl Port {
             width: orig.width,
             direction: orig.direction.clone(),
             parent: new_parent,
-            attributes: orig.attributes.clone(),
+            attributes: translator.translate_attributes(&orig.attributes),
+            uid: translator.next_uid(),
         }
     }
 
+    /// A small identifier for this port which, unlike its address, is the
+    /// same on every run of the same program. Cells and ports are numbered
+    /// together in the order they are translated, so no two share an id.
+    pub fn uid(&self) -> u32 {
+        self.uid
+    }
+
     /// Get the canonical representation for this Port.
     pub fn canonical(&self) -> Canonical {
         Canonical(self.get_parent_name(), self.name)
     }
-    /// Gets name of parent object.
+
+    /// The canonical name of this port as a string of the form
+    /// `parent.name`. This can be turned back into the port with
+    /// [super::Component::resolve_canonical].
+    pub fn canonical_name(&self) -> String {
+        canonical_name(&self.canonical())
+    }
+
+    /// Gets name of parent object. If the parent has already been dropped,
+    /// as can happen while a simulation is torn down, a placeholder name is
+    /// returned instead.
     pub fn get_parent_name(&self) -> Id {
-        match &self.parent {
-            PortParent::Cell(cell) => cell.upgrade().read().name,
-            PortParent::Group(group) => group.upgrade().read().name,
-        }
+        self.parent
+            .try_name()
+            .unwrap_or_else(|| Id::from(DROPPED_PARENT))
+    }
+
+    /// Returns true if this port is a hole of a group or static group.
+    pub fn is_hole(&self) -> bool {
+        matches!(
+            self.parent,
+            PortParent::Group(_) | PortParent::StaticGroup(_)
+        )
+    }
+
+    /// Returns true if this port belongs to a cell, including the signature
+    /// cell of a component. This is the complement of [Port::is_hole].
+    pub fn is_cell_port(&self) -> bool {
+        matches!(self.parent, PortParent::Cell(_))
+    }
+
+    /// Return the value associated with this attribute key.
+    pub fn get_attribute<A: Into<Attribute>>(&self, attr: A) -> Option<u64> {
+        self.attributes.get(attr.into())
+    }
+
+    /// Returns true if this port has the attribute `attr`.
+    pub fn has_attribute<A: Into<Attribute>>(&self, attr: A) -> bool {
+        self.attributes.has(attr.into())
     }
 }
 
+/// Formats a [Canonical] port reference as `parent.name`.
+pub fn canonical_name(canonical: &Canonical) -> String {
+    format!("{}.{}", canonical.0, canonical.1)
+}
+
 /// A Group of assignments that perform a logical action.
 #[derive(Debug, Clone)]
 #[cfg_attr(feature = "serialize", derive(serde::Serialize))]
@@ -102,16 +203,70 @@ pub struct Group {
     pub assignments: Vec<Assignment<Nothing>>,
 
     /// Holes for this group
+    #[cfg_attr(
+        feature = "serialize",
+        serde(serialize_with = "serialize_holes")
+    )]
     pub holes: SmallVec<[ArcTex<Port>; 3]>,
 
     /// Attributes for this group.
     pub attributes: Attributes,
 }
 
+/// Orders holes with `go` first, then `done`, then any others by name.
+fn sort_holes(holes: &[ArcTex<Port>]) -> Vec<ArcTex<Port>> {
+    let mut sorted = holes.to_vec();
+    sorted.sort_by_cached_key(|hole| {
+        let name = hole.read().name;
+        let rank = if name == "go" {
+            0
+        } else if name == "done" {
+            1
+        } else {
+            2
+        };
+        (rank, name.to_string())
+    });
+    sorted
+}
+
+/// Serializes holes in the deterministic order given by [Group::holes_sorted]
+/// rather than translation order.
+#[cfg(feature = "serialize")]
+fn serialize_holes<S>(
+    holes: &SmallVec<[ArcTex<Port>; 3]>,
+    serializer: S,
+) -> Result<S::Ok, S::Error>
+where
+    S: serde::Serializer,
+{
+    serde::Serialize::serialize(&sort_holes(holes), serializer)
+}
+
 impl Group {
+    /// Creates an empty group with no holes or assignments
+    pub(crate) fn new(name: Id) -> Self {
+        Self {
+            name,
+            assignments: vec![],
+            holes: Default::default(),
+            attributes: Default::default(),
+        }
+    }
+
+    /// Copies everything but the holes and assignments
+    pub(crate) fn clone_partial(&self) -> Self {
+        Self {
+            name: self.name,
+            assignments: vec![],
+            holes: Default::default(),
+            attributes: self.attributes.clone(),
+        }
+    }
+
     pub(crate) fn from_ir_partial(
         original: &RRC<orig_ir::Group>,
-        _translator: &mut TranslationMap,
+        translator: &mut TranslationMap,
     ) -> Self {
         let orig = original.borrow();
 
@@ -119,7 +274,7 @@ impl Group {
             name: orig.name(),
             assignments: vec![],
             holes: Default::default(),
-            attributes: orig.attributes.clone(),
+            attributes: translator.translate_attributes(&orig.attributes),
         }
     }
 
@@ -149,6 +304,23 @@ impl Group {
     pub fn name(&self) -> Id {
         self.name
     }
+
+    /// Returns the holes of this group in a deterministic order: `go`, then
+    /// `done`, then any remaining holes sorted by name.
+    pub fn holes_sorted(&self) -> Vec<ArcTex<Port>> {
+        sort_holes(&self.holes)
+    }
+
+    /// Returns the assignments of this group ordered so that each comes after
+    /// the assignments in the group which write the ports it reads, either
+    /// as its source or in its guard. Evaluating in this order cuts down the
+    /// passes needed for values to settle. Errors if the assignments of the
+    /// group form a cycle.
+    pub fn ordered_assignments(
+        &self,
+    ) -> InterpreterResult<Vec<&Assignment<Nothing>>> {
+        comb_graph::order_assignments(&self.assignments)
+    }
 }
 
 impl GetName for Group {
@@ -157,6 +329,102 @@ impl GetName for Group {
     }
 }
 
+/// A Group of assignments with a fixed latency.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize))]
+pub struct StaticGroup {
+    /// Name of this group
+    name: Id,
+
+    /// The assignments used in this group
+    pub assignments: Vec<Assignment<StaticTiming>>,
+
+    /// Holes for this group
+    #[cfg_attr(
+        feature = "serialize",
+        serde(serialize_with = "serialize_holes")
+    )]
+    pub holes: SmallVec<[ArcTex<Port>; 3]>,
+
+    /// Attributes for this group.
+    pub attributes: Attributes,
+
+    /// The number of cycles this group takes to execute
+    pub latency: u64,
+}
+
+impl StaticGroup {
+    /// Copies everything but the holes and assignments
+    pub(crate) fn clone_partial(&self) -> Self {
+        Self {
+            name: self.name,
+            assignments: vec![],
+            holes: Default::default(),
+            attributes: self.attributes.clone(),
+            latency: self.latency,
+        }
+    }
+
+    pub(crate) fn from_ir_partial(
+        original: &RRC<orig_ir::StaticGroup>,
+        translator: &mut TranslationMap,
+    ) -> Self {
+        let orig = original.borrow();
+
+        Self {
+            name: orig.name(),
+            assignments: vec![],
+            holes: Default::default(),
+            attributes: translator.translate_attributes(&orig.attributes),
+            latency: orig.get_latency(),
+        }
+    }
+
+    /// Get a reference to the named hole if it exists.
+    pub fn find<S>(&self, name: S) -> Option<ArcTex<Port>>
+    where
+        S: std::fmt::Display,
+        Id: PartialEq<S>,
+    {
+        self.holes
+            .iter()
+            .find(|&g| g.read().name == name)
+            .map(Arc::clone)
+    }
+
+    /// Get a reference to the named hole or panic.
+    pub fn get<S>(&self, name: S) -> ArcTex<Port>
+    where
+        S: std::fmt::Display + Clone,
+        Id: PartialEq<S>,
+    {
+        self.find(name.clone()).unwrap_or_else(|| {
+            panic!("Hole `{name}' not found on static group `{}'", self.name)
+        })
+    }
+
+    pub fn name(&self) -> Id {
+        self.name
+    }
+
+    /// The number of cycles this group takes to execute
+    pub fn get_latency(&self) -> u64 {
+        self.latency
+    }
+
+    /// Returns the holes of this group in a deterministic order: `go`, then
+    /// `done`, then any remaining holes sorted by name.
+    pub fn holes_sorted(&self) -> Vec<ArcTex<Port>> {
+        sort_holes(&self.holes)
+    }
+}
+
+impl GetName for StaticGroup {
+    fn name(&self) -> Id {
+        self.name
+    }
+}
+
 /// Represents a guarded assignment in the program
 #[derive(Clone, Debug)]
 #[cfg_attr(feature = "serialize", derive(serde::Serialize))]
@@ -167,27 +435,40 @@ pub struct Assignment<T> {
     /// The source for the assignment.
     pub src: ArcTex<Port>,
 
-    /// The guard for this assignment.
-    pub guard: Box<Guard<T>>,
+    /// The guard for this assignment. Structurally identical guards are
+    /// interned by the [TranslationMap] and share a single allocation.
+    pub guard: Arc<Guard<T>>,
 
     /// Attributes for this assignment.
     pub attributes: Attributes,
 }
 
-impl<T: Clone> Assignment<T> {
+impl<T: Clone + ToString + Send + Sync + 'static> Assignment<T> {
     pub(crate) fn from_ir(
         original: &orig_ir::Assignment<T>,
         translator: &mut TranslationMap,
     ) -> Self {
+        let guard = Guard::from_ir(&original.guard, translator);
         Self {
             dst: translator.get_port(&original.dst),
             src: translator.get_port(&original.src),
-            guard: Box::new(Guard::from_ir(&original.guard, translator)),
-            attributes: original.attributes.clone(),
+            guard: translator.intern_guard(guard),
+            attributes: translator.translate_attributes(&original.attributes),
         }
     }
 }
 
+/// The latencies of the pipelined primitives implemented by the interpreter.
+/// Each of these produces its result on the third cycle after its inputs are
+/// latched. The dividers are absent since they take a data-dependent number
+/// of cycles and signal completion with `done`.
+const PIPELINE_LATENCIES: [(&str, u64); 4] = [
+    ("std_mult_pipe", 3),
+    ("std_smult_pipe", 3),
+    ("std_fp_mult_pipe", 3),
+    ("std_fp_smult_pipe", 3),
+];
+
 /// Represents an instantiated cell.
 #[derive(Debug)]
 #[cfg_attr(feature = "serialize", derive(serde::Serialize))]
@@ -201,7 +482,9 @@ pub struct Cell {
     /// Attributes for this group.
     pub attributes: Attributes,
     /// Whether the cell is external
-    _reference: bool,
+    reference: bool,
+    /// Identifier assigned in translation order, see [Cell::uid]
+    pub(crate) uid: u32,
 }
 
 impl GetName for Cell {
@@ -211,9 +494,33 @@ impl GetName for Cell {
 }
 
 impl Cell {
+    /// Creates a cell with no ports
+    pub(crate) fn new(name: Id, prototype: CellType) -> Self {
+        Self {
+            name,
+            ports: Default::default(),
+            prototype,
+            attributes: Default::default(),
+            reference: false,
+            uid: 0,
+        }
+    }
+
+    /// Copies everything but the ports
+    pub(crate) fn clone_partial(&self) -> Self {
+        Self {
+            name: self.name,
+            ports: Default::default(),
+            prototype: self.prototype.clone(),
+            attributes: self.attributes.clone(),
+            reference: self.reference,
+            uid: self.uid,
+        }
+    }
+
     pub(crate) fn from_ir_partial(
         original: &RRC<orig_ir::Cell>,
-        _translator: &mut TranslationMap,
+        translator: &mut TranslationMap,
     ) -> Self {
         let orig = original.borrow();
 
@@ -221,11 +528,32 @@ impl Cell {
             name: orig.name(),
             ports: Default::default(),
             prototype: orig.prototype.clone(),
-            attributes: orig.attributes.clone(),
-            _reference: orig.is_reference(),
+            attributes: translator.translate_attributes(&orig.attributes),
+            reference: orig.is_reference(),
+            uid: translator.next_uid(),
         }
     }
 
+    /// A small identifier for this cell which, unlike its address, is the
+    /// same on every run of the same program. See [Port::uid].
+    pub fn uid(&self) -> u32 {
+        self.uid
+    }
+
+    /// Orders the ports of this cell by direction, inputs first and then
+    /// outputs, and by name within each direction.
+    pub fn sort_ports(&mut self) {
+        self.ports.sort_by_cached_key(|port| {
+            let port = port.read();
+            let rank = match port.direction {
+                Direction::Input => 0,
+                Direction::Output => 1,
+                Direction::Inout => 2,
+            };
+            (rank, port.name.to_string())
+        });
+    }
+
     /// Returns a reference to all [super::Port] attached to this cells.
     pub fn ports(&self) -> &SmallVec<[ArcTex<Port>; 10]> {
         &self.ports
@@ -261,9 +589,53 @@ impl Cell {
         })
     }
 
+    /// Get the width of the named port if it exists.
+    pub fn width_of_port<S>(&self, name: S) -> Option<u64>
+    where
+        S: std::fmt::Display + Clone,
+        Id: PartialEq<S>,
+    {
+        self.find(name).map(|p| p.read().width)
+    }
+
     pub fn name(&self) -> Id {
         self.name
     }
+
+    /// Returns true if this cell is an instance of a primitive.
+    pub fn is_primitive(&self) -> bool {
+        matches!(self.prototype, CellType::Primitive { .. })
+    }
+
+    /// Returns true if this cell is an instance of a user-defined component.
+    pub fn is_component(&self) -> bool {
+        matches!(self.prototype, CellType::Component { .. })
+    }
+
+    /// Returns true if this is a `ref` cell, i.e. one which is supplied by
+    /// the caller when the component is invoked.
+    pub fn is_reference(&self) -> bool {
+        self.reference
+    }
+
+    /// Returns true if this cell is a constant.
+    pub fn is_constant(&self) -> bool {
+        matches!(self.prototype, CellType::Constant { .. })
+    }
+
+    /// Returns true if this cell is the signature of the enclosing component.
+    pub fn is_this_component(&self) -> bool {
+        matches!(self.prototype, CellType::ThisComponent)
+    }
+
+    /// Returns the name of the primitive this cell instantiates, if any.
+    pub fn primitive_name(&self) -> Option<Id> {
+        match &self.prototype {
+            CellType::Primitive { name, .. } => Some(*name),
+            _ => None,
+        }
+    }
+
     /// Get parameter binding from the prototype used to build this cell.
     pub fn get_parameter<S>(&self, param: S) -> Option<u64>
     where
@@ -280,6 +652,40 @@ impl Cell {
         }
     }
 
+    /// Returns every parameter binding from the prototype used to build this
+    /// cell. Cells which are not primitives have no parameters.
+    pub fn parameters(&self) -> impl Iterator<Item = (Id, u64)> + '_ {
+        let binding: &[(Id, u64)] = match &self.prototype {
+            CellType::Primitive { param_binding, .. } => &param_binding[..],
+            _ => &[],
+        };
+        binding.iter().copied()
+    }
+
+    /// Returns the number of cycles a pipelined primitive takes to produce its
+    /// result, or `None` if this cell is not a known pipelined primitive.
+    ///
+    /// A `@static` attribute on the cell takes precedence, followed by the
+    /// latency declared on the primitive definition. Otherwise the latency is
+    /// looked up in a table of the pipelined primitives the interpreter
+    /// implements.
+    pub fn pipeline_latency(&self) -> Option<u64> {
+        if let Some(latency) = self.get_attribute(NumAttr::Static) {
+            return Some(latency);
+        }
+
+        match &self.prototype {
+            CellType::Primitive {
+                latency: Some(latency),
+                ..
+            } => Some(latency.get()),
+            CellType::Primitive { name, .. } => PIPELINE_LATENCIES
+                .iter()
+                .find_map(|(prim, latency)| (name == prim).then_some(*latency)),
+            _ => None,
+        }
+    }
+
     /// Return the value associated with this attribute key.
     pub fn get_attribute<A: Into<Attribute>>(&self, attr: A) -> Option<u64> {
         self.attributes.get(attr.into())
@@ -300,6 +706,27 @@ impl Cell {
             .map(Arc::clone)
     }
 
+    /// Iterates over the input ports of this cell, which are driven by
+    /// assignments and read by the cell.
+    pub fn input_ports(&self) -> impl Iterator<Item = &ArcTex<Port>> + '_ {
+        self.ports_with_direction(Direction::Input)
+    }
+
+    /// Iterates over the output ports of this cell, which the cell writes and
+    /// assignments read.
+    pub fn output_ports(&self) -> impl Iterator<Item = &ArcTex<Port>> + '_ {
+        self.ports_with_direction(Direction::Output)
+    }
+
+    fn ports_with_direction(
+        &self,
+        direction: Direction,
+    ) -> impl Iterator<Item = &ArcTex<Port>> + '_ {
+        self.ports
+            .iter()
+            .filter(move |p| p.read().direction == direction)
+    }
+
     /// Return the unique port with the given attribute.
     /// If multiple ports have the same attribute, then we panic.
     /// If there are not ports with the give attribute, then we return None.
@@ -344,6 +771,25 @@ impl Cell {
     }
 }
 
+/// Applies the comparison `op` to `lhs` and `rhs`, treating both as unsigned.
+/// Values of different widths are compared by their numeric value, as if the
+/// narrower one were zero-extended.
+pub fn apply_port_comp(op: &PortComp, lhs: &Value, rhs: &Value) -> bool {
+    let ord = if lhs.width() == rhs.width() {
+        lhs.partial_cmp(rhs).unwrap()
+    } else {
+        lhs.as_unsigned().cmp(&rhs.as_unsigned())
+    };
+    match op {
+        PortComp::Eq => ord.is_eq(),
+        PortComp::Neq => ord.is_ne(),
+        PortComp::Gt => ord.is_gt(),
+        PortComp::Lt => ord.is_lt(),
+        PortComp::Geq => ord.is_ge(),
+        PortComp::Leq => ord.is_le(),
+    }
+}
+
 /// An assignment guard which has pointers to the various ports from which it reads.
 #[derive(Debug, Clone, Default)]
 #[cfg_attr(feature = "serialize", derive(serde::Serialize))]
@@ -357,6 +803,9 @@ pub enum Guard<T> {
     #[default]
     /// The constant true
     True,
+    /// The constant false. Calyx has no such guard so this never comes from
+    /// translation, it only appears as the result of [Guard::simplified].
+    False,
     /// Comparison operator.
     CompOp(PortComp, ArcTex<Port>, ArcTex<Port>),
     /// Uses the value on a port as the condition. Same as `p1 == true`
@@ -392,6 +841,170 @@ impl<T: Clone> Guard<T> {
             orig_ir::Guard::Info(i) => Guard::Info(i.clone()),
         }
     }
+
+    /// Returns an equivalent guard with constants folded away. `True`
+    /// conjuncts and `False` disjuncts are dropped, a `True` disjunct or
+    /// `False` conjunct absorbs the whole expression, and double negations
+    /// are collapsed. The result is already fully simplified, so simplifying
+    /// it again has no effect.
+    pub fn simplified(&self) -> Guard<T> {
+        match self {
+            Guard::And(l, r) => match (l.simplified(), r.simplified()) {
+                (Guard::False, _) | (_, Guard::False) => Guard::False,
+                (Guard::True, g) | (g, Guard::True) => g,
+                (l, r) => Guard::And(l.into(), r.into()),
+            },
+            Guard::Or(l, r) => match (l.simplified(), r.simplified()) {
+                (Guard::True, _) | (_, Guard::True) => Guard::True,
+                (Guard::False, g) | (g, Guard::False) => g,
+                (l, r) => Guard::Or(l.into(), r.into()),
+            },
+            Guard::Not(g) => match g.simplified() {
+                Guard::True => Guard::False,
+                Guard::False => Guard::True,
+                Guard::Not(inner) => *inner,
+                g => Guard::Not(g.into()),
+            },
+            _ => self.clone(),
+        }
+    }
+}
+
+impl<T> Guard<T> {
+    /// Evaluates the guard using `read` to look up the value of each port it
+    /// reads. Comparisons are unsigned and `Info` nodes are treated as true.
+    /// Panics if a lone port in the guard is not exactly one bit wide.
+    pub fn eval<F>(&self, read: F) -> bool
+    where
+        F: Fn(&ArcTex<Port>) -> Value,
+    {
+        self.eval_with(&read)
+    }
+
+    /// Returns every port read by this guard, without duplicates, in the
+    /// order they are first encountered.
+    pub fn referenced_ports(&self) -> Vec<ArcTex<Port>> {
+        let mut seen = HashSet::new();
+        let mut out = vec![];
+        self.collect_ports(&mut seen, &mut out);
+        out
+    }
+
+    fn collect_ports(
+        &self,
+        seen: &mut HashSet<*const Port>,
+        out: &mut Vec<ArcTex<Port>>,
+    ) {
+        let mut push = |port: &ArcTex<Port>| {
+            if seen.insert(port.data_ptr()) {
+                out.push(Arc::clone(port))
+            }
+        };
+        match self {
+            Guard::Or(l, r) | Guard::And(l, r) => {
+                l.collect_ports(seen, out);
+                r.collect_ports(seen, out);
+            }
+            Guard::Not(g) => g.collect_ports(seen, out),
+            Guard::CompOp(_, l, r) => {
+                push(l);
+                push(r);
+            }
+            Guard::Port(p) => push(p),
+            Guard::True | Guard::False | Guard::Info(_) => {}
+        }
+    }
+
+    fn eval_with<F>(&self, read: &F) -> bool
+    where
+        F: Fn(&ArcTex<Port>) -> Value,
+    {
+        match self {
+            Guard::Or(l, r) => l.eval_with(read) || r.eval_with(read),
+            Guard::And(l, r) => l.eval_with(read) && r.eval_with(read),
+            Guard::Not(g) => !g.eval_with(read),
+            Guard::True | Guard::Info(_) => true,
+            Guard::False => false,
+            Guard::CompOp(op, l, r) => apply_port_comp(op, &read(l), &read(r)),
+            Guard::Port(p) => read(p).as_bool(),
+        }
+    }
+}
+
+impl<T: ToString> Guard<T> {
+    /// Writes this guard as an operand of an `&`, `|`, or `!`, adding
+    /// parentheses unless it is atomic or is the same operator as `parent`.
+    fn fmt_operand(
+        &self,
+        parent: Option<&Guard<T>>,
+        f: &mut std::fmt::Formatter<'_>,
+    ) -> std::fmt::Result {
+        let same_op = matches!(
+            (self, parent),
+            (Guard::And(..), Some(Guard::And(..)))
+                | (Guard::Or(..), Some(Guard::Or(..)))
+        );
+        match self {
+            Guard::And(..) | Guard::Or(..) | Guard::CompOp(..) if !same_op => {
+                write!(f, "({self})")
+            }
+            _ => write!(f, "{self}"),
+        }
+    }
+}
+
+/// Formats the guard in Calyx syntax, naming ports canonically
+impl<T: ToString> std::fmt::Display for Guard<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Guard::Or(l, r) | Guard::And(l, r) => {
+                let op = if matches!(self, Guard::Or(..)) {
+                    "|"
+                } else {
+                    "&"
+                };
+                l.fmt_operand(Some(self), f)?;
+                write!(f, " {op} ")?;
+                r.fmt_operand(Some(self), f)
+            }
+            Guard::Not(g) => {
+                write!(f, "!")?;
+                g.fmt_operand(None, f)
+            }
+            Guard::True => write!(f, "1"),
+            Guard::False => write!(f, "0"),
+            Guard::CompOp(op, l, r) => {
+                let op = match op {
+                    PortComp::Eq => "==",
+                    PortComp::Neq => "!=",
+                    PortComp::Gt => ">",
+                    PortComp::Lt => "<",
+                    PortComp::Geq => ">=",
+                    PortComp::Leq => "<=",
+                };
+                write!(
+                    f,
+                    "{} {op} {}",
+                    l.read().canonical_name(),
+                    r.read().canonical_name()
+                )
+            }
+            Guard::Port(p) => write!(f, "{}", p.read().canonical_name()),
+            Guard::Info(i) => write!(f, "{}", i.to_string()),
+        }
+    }
+}
+
+/// Formats the assignment in Calyx syntax as `dst = guard ? src;`, leaving
+/// out the guard when it is always true.
+impl<T: ToString> std::fmt::Display for Assignment<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} = ", self.dst.read().canonical_name())?;
+        if !matches!(*self.guard, Guard::True) {
+            write!(f, "{} ? ", self.guard)?;
+        }
+        write!(f, "{};", self.src.read().canonical_name())
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -420,7 +1033,7 @@ impl CombGroup {
                 .iter()
                 .map(|x| Assignment::from_ir(x, translator))
                 .collect(),
-            attributes: orig.attributes.clone(),
+            attributes: translator.translate_attributes(&orig.attributes),
         }
     }
 
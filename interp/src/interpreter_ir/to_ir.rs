@@ -0,0 +1,250 @@
+use calyx_ir::{self as orig_ir, Control as CalyxControl, RRC};
+use calyx_utils::Id;
+
+use crate::errors::{InterpreterError, InterpreterResult};
+use crate::utils::ArcTex;
+
+use super::control::*;
+use super::structure::*;
+
+/// Converts interpreter control back into the Calyx IR of `comp`. Groups,
+/// cells, and ports are looked up by name in `comp`, so the control may be
+/// converted into any component which defines the same structures, not only
+/// the one it was translated from.
+pub(super) struct IrResolver<'a> {
+    pub comp: &'a orig_ir::Component,
+}
+
+fn unresolved<T>(kind: &'static str, name: String) -> InterpreterResult<T> {
+    Err(InterpreterError::UnresolvedIrReference { kind, name }.into())
+}
+
+impl IrResolver<'_> {
+    fn group(
+        &self,
+        group: &ArcTex<Group>,
+    ) -> InterpreterResult<RRC<orig_ir::Group>> {
+        let name = group.read().name();
+        match self.comp.find_group(name) {
+            Some(g) => Ok(g),
+            None => unresolved("group", name.to_string()),
+        }
+    }
+
+    fn static_group(
+        &self,
+        group: &ArcTex<StaticGroup>,
+    ) -> InterpreterResult<RRC<orig_ir::StaticGroup>> {
+        let name = group.read().name();
+        match self.comp.find_static_group(name) {
+            Some(g) => Ok(g),
+            None => unresolved("static group", name.to_string()),
+        }
+    }
+
+    fn comb_group(
+        &self,
+        group: &ArcTex<CombGroup>,
+    ) -> InterpreterResult<RRC<orig_ir::CombGroup>> {
+        let name = group.read().name();
+        match self.comp.find_comb_group(name) {
+            Some(g) => Ok(g),
+            None => unresolved("comb group", name.to_string()),
+        }
+    }
+
+    fn cell(
+        &self,
+        cell: &ArcTex<Cell>,
+    ) -> InterpreterResult<RRC<orig_ir::Cell>> {
+        let cell = cell.read();
+        if cell.is_this_component() {
+            return Ok(self.comp.signature.clone());
+        }
+        match self.comp.find_cell(cell.name()) {
+            Some(c) => Ok(c),
+            None => unresolved("cell", cell.name().to_string()),
+        }
+    }
+
+    fn port(
+        &self,
+        port: &ArcTex<Port>,
+    ) -> InterpreterResult<RRC<orig_ir::Port>> {
+        let port = port.read();
+        let found = match &port.parent {
+            PortParent::Cell(c) => {
+                self.cell(&c.upgrade())?.borrow().find(port.name)
+            }
+            PortParent::Group(g) => {
+                self.group(&g.upgrade())?.borrow().find(port.name)
+            }
+            PortParent::StaticGroup(g) => {
+                self.static_group(&g.upgrade())?.borrow().find(port.name)
+            }
+        };
+        match found {
+            Some(p) => Ok(p),
+            None => unresolved("port", port.canonical_name()),
+        }
+    }
+
+    fn port_map(
+        &self,
+        ports: &[(Id, ArcTex<Port>)],
+    ) -> InterpreterResult<Vec<(Id, RRC<orig_ir::Port>)>> {
+        ports
+            .iter()
+            .map(|(id, p)| Ok((*id, self.port(p)?)))
+            .collect()
+    }
+
+    fn cell_map(
+        &self,
+        cells: &[(Id, ArcTex<Cell>)],
+    ) -> InterpreterResult<Vec<(Id, RRC<orig_ir::Cell>)>> {
+        cells
+            .iter()
+            .map(|(id, c)| Ok((*id, self.cell(c)?)))
+            .collect()
+    }
+
+    pub fn control(
+        &self,
+        control: &Control,
+    ) -> InterpreterResult<CalyxControl> {
+        Ok(match control {
+            Control::Seq(s) => CalyxControl::Seq(orig_ir::Seq {
+                stmts: s
+                    .stmts
+                    .iter()
+                    .map(|x| self.control(x))
+                    .collect::<InterpreterResult<_>>()?,
+                attributes: s.attributes.clone(),
+            }),
+            Control::Par(p) => CalyxControl::Par(orig_ir::Par {
+                stmts: p
+                    .stmts
+                    .iter()
+                    .map(|x| self.control(x))
+                    .collect::<InterpreterResult<_>>()?,
+                attributes: p.attributes.clone(),
+            }),
+            Control::If(i) => CalyxControl::If(orig_ir::If {
+                port: self.port(&i.port)?,
+                cond: i
+                    .cond
+                    .as_ref()
+                    .map(|x| self.comb_group(x))
+                    .transpose()?,
+                tbranch: Box::new(self.control(&i.tbranch)?),
+                fbranch: Box::new(self.control(&i.fbranch)?),
+                attributes: i.attributes.clone(),
+            }),
+            Control::While(w) => CalyxControl::While(orig_ir::While {
+                port: self.port(&w.port)?,
+                cond: w
+                    .cond
+                    .as_ref()
+                    .map(|x| self.comb_group(x))
+                    .transpose()?,
+                body: Box::new(self.control(&w.body)?),
+                attributes: w.attributes.clone(),
+            }),
+            Control::Repeat(r) => CalyxControl::Repeat(orig_ir::Repeat {
+                body: Box::new(self.control(&r.body)?),
+                num_repeats: r.num_repeats,
+                attributes: r.attributes.clone(),
+            }),
+            Control::Invoke(i) => CalyxControl::Invoke(orig_ir::Invoke {
+                comp: self.cell(&i.comp)?,
+                inputs: self.port_map(&i.inputs)?,
+                outputs: self.port_map(&i.outputs)?,
+                attributes: i.attributes.clone(),
+                comb_group: i
+                    .comb_group
+                    .as_ref()
+                    .map(|x| self.comb_group(x))
+                    .transpose()?,
+                ref_cells: self.cell_map(&i.ref_cells)?,
+            }),
+            Control::Enable(e) => CalyxControl::Enable(orig_ir::Enable {
+                group: self.group(&e.group)?,
+                attributes: e.attributes.clone(),
+            }),
+            Control::Empty(e) => CalyxControl::Empty(Empty::clone(e)),
+            Control::Static(s) => CalyxControl::Static(self.static_control(s)?),
+        })
+    }
+
+    fn static_control(
+        &self,
+        control: &StaticControl,
+    ) -> InterpreterResult<orig_ir::StaticControl> {
+        Ok(match control {
+            StaticControl::Repeat(r) => {
+                orig_ir::StaticControl::Repeat(orig_ir::StaticRepeat {
+                    attributes: r.attributes.clone(),
+                    body: Box::new(self.static_control(&r.body)?),
+                    num_repeats: r.num_repeats,
+                    latency: r.latency,
+                })
+            }
+            StaticControl::Enable(e) => {
+                orig_ir::StaticControl::Enable(orig_ir::StaticEnable {
+                    group: self.static_group(&e.group)?,
+                    attributes: e.attributes.clone(),
+                })
+            }
+            StaticControl::Par(p) => {
+                orig_ir::StaticControl::Par(orig_ir::StaticPar {
+                    stmts: p
+                        .stmts
+                        .iter()
+                        .map(|x| self.static_control(x))
+                        .collect::<InterpreterResult<_>>()?,
+                    attributes: p.attributes.clone(),
+                    latency: p.latency,
+                })
+            }
+            StaticControl::Seq(s) => {
+                orig_ir::StaticControl::Seq(orig_ir::StaticSeq {
+                    stmts: s
+                        .stmts
+                        .iter()
+                        .map(|x| self.static_control(x))
+                        .collect::<InterpreterResult<_>>()?,
+                    attributes: s.attributes.clone(),
+                    latency: s.latency,
+                })
+            }
+            StaticControl::If(i) => {
+                orig_ir::StaticControl::If(orig_ir::StaticIf {
+                    port: self.port(&i.port)?,
+                    latency: i.latency,
+                    tbranch: Box::new(self.static_control(&i.tbranch)?),
+                    fbranch: Box::new(self.static_control(&i.fbranch)?),
+                    attributes: i.attributes.clone(),
+                })
+            }
+            StaticControl::Empty(e) => {
+                orig_ir::StaticControl::Empty(Empty::clone(e))
+            }
+            StaticControl::Invoke(i) => {
+                orig_ir::StaticControl::Invoke(orig_ir::StaticInvoke {
+                    comp: self.cell(&i.comp)?,
+                    latency: i.latency,
+                    inputs: self.port_map(&i.inputs)?,
+                    outputs: self.port_map(&i.outputs)?,
+                    attributes: i.attributes.clone(),
+                    ref_cells: self.cell_map(&i.ref_cells)?,
+                    comb_group: i
+                        .comb_group
+                        .as_ref()
+                        .map(|x| self.comb_group(x))
+                        .transpose()?,
+                })
+            }
+        })
+    }
+}
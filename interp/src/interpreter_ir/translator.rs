@@ -1,62 +1,277 @@
+use super::component::Component;
 use super::structure::*;
 use crate::utils::{arctex, ArcTex, AsRaw};
 use ahash::HashMap;
-use calyx_ir::{self as orig_ir, RRC};
+use calyx_ir::{self as orig_ir, Attribute, Attributes, PortComp, RRC};
+use parking_lot::RwLock;
+use rayon::prelude::*;
+use std::any::{Any, TypeId};
+use std::collections::hash_map::{DefaultHasher, Entry};
+use std::hash::{Hash, Hasher};
+use std::mem::Discriminant;
+use std::sync::Arc;
+
+/// The attributes which carry semantic meaning for the interpreter and are
+/// therefore kept even when the translator is dropping attributes.
+const PRESERVED_ATTRIBUTES: [Attribute; 4] = [
+    Attribute::Num(orig_ir::NumAttr::Go),
+    Attribute::Num(orig_ir::NumAttr::Done),
+    Attribute::Bool(orig_ir::BoolAttr::External),
+    Attribute::Num(orig_ir::NumAttr::Static),
+];
+
+/// The number of independently locked parts of each [ShardedMap]
+const SHARDS: usize = 16;
 
 #[derive(Debug, Default)]
 pub struct TranslationMap {
-    cell_map: HashMap<*const orig_ir::Cell, ArcTex<Cell>>,
-    port_map: HashMap<*const orig_ir::Port, ArcTex<Port>>,
-    group_map: HashMap<*const orig_ir::Group, ArcTex<Group>>,
-    comb_group_map: HashMap<*const orig_ir::CombGroup, ArcTex<CombGroup>>,
+    /// The translations, shared with every fork of this map. See
+    /// [TranslationMap::translate_components_parallel]
+    tables: Arc<Tables>,
+    /// The cells and ports translated by this map, if it is a fork
+    fork_log: Option<ForkLog>,
+    /// When true, only the [PRESERVED_ATTRIBUTES] are copied into the
+    /// translated structures
+    drop_attributes: bool,
+    /// Lookups answered by an existing translation
+    hits: usize,
+    /// Lookups which constructed a fresh translation
+    misses: usize,
+    /// The next id to hand out to a translated cell or port
+    next_uid: u32,
+}
+
+/// The translated structures, keyed by the address of their original. The
+/// tables are locked so that several forks of a [TranslationMap] can insert
+/// into them at once.
+#[derive(Debug, Default)]
+struct Tables {
+    cell_map: ShardedMap<usize, ArcTex<Cell>>,
+    port_map: ShardedMap<usize, ArcTex<Port>>,
+    group_map: ShardedMap<usize, ArcTex<Group>>,
+    comb_group_map: ShardedMap<usize, ArcTex<CombGroup>>,
+    static_group_map: ShardedMap<usize, ArcTex<StaticGroup>>,
+    /// Inverse of `cell_map`, from translated cells to their originals
+    cell_origins: ShardedMap<usize, usize>,
+    /// Inverse of `port_map`, from translated ports to their originals
+    port_origins: ShardedMap<usize, usize>,
+    guards: GuardInterner,
 }
 
+/// The cells and ports first translated by a fork. A fork numbers them from
+/// zero, and they are renumbered when it is joined so that the ids match a
+/// sequential translation.
+#[derive(Debug, Default)]
+struct ForkLog {
+    cells: Vec<ArcTex<Cell>>,
+    ports: Vec<ArcTex<Port>>,
+}
+
+/// A snapshot of the size of a [TranslationMap] and how often its lookups
+/// were answered by an existing translation.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct TranslationStats {
+    pub cells: usize,
+    pub ports: usize,
+    pub groups: usize,
+    pub comb_groups: usize,
+    pub static_groups: usize,
+    /// Lookups which returned an already translated structure
+    pub hits: usize,
+    /// Lookups which constructed a new structure
+    pub misses: usize,
+}
+
+/// A reference to a component of the source IR which may be sent to another
+/// thread. See [TranslationMap::translate_components_parallel].
+struct SourceComponent<'a>(&'a orig_ir::Component);
+
+// SAFETY: the source IR is not thread safe because its `Rc` reference counts
+// and `RefCell` borrow flags are updated without synchronization. The cells,
+// ports, groups, and control of a component only ever point to structures of
+// the same component; other components are referred to by name. A component
+// is handed to a single worker, which only follows pointers out of that
+// component, and the caller cannot touch the components while they are
+// borrowed by the translation. So no two threads update the same count or
+// flag.
+unsafe impl Send for SourceComponent<'_> {}
+
 impl TranslationMap {
     pub fn new() -> Self {
         Default::default()
     }
+
+    /// Sets whether the translator should drop all attributes other than the
+    /// ones needed by the interpreter (`@go`, `@done`, `@external`, and
+    /// `@static`). This saves memory on large designs when the attributes are
+    /// not inspected at runtime.
+    pub fn drop_attributes(mut self, value: bool) -> Self {
+        self.drop_attributes = value;
+        self
+    }
+
+    /// Produces the attributes to store on a translated structure. This is a
+    /// plain clone unless the translator is dropping attributes.
+    pub fn translate_attributes(&self, original: &Attributes) -> Attributes {
+        if !self.drop_attributes {
+            return original.clone();
+        }
+
+        let mut attrs = Attributes::default();
+        for attr in PRESERVED_ATTRIBUTES {
+            if let Some(val) = original.get(attr) {
+                attrs.insert(attr, val);
+            }
+        }
+        attrs
+    }
+    /// Hands out the ids of translated cells and ports in increasing order
+    pub(crate) fn next_uid(&mut self) -> u32 {
+        let uid = self.next_uid;
+        self.next_uid += 1;
+        uid
+    }
+
+    /// Reports the number of entries in each table of the map along with the
+    /// number of lookups served from the tables versus freshly translated.
+    pub fn stats(&self) -> TranslationStats {
+        TranslationStats {
+            cells: self.tables.cell_map.len(),
+            ports: self.tables.port_map.len(),
+            groups: self.tables.group_map.len(),
+            comb_groups: self.tables.comb_group_map.len(),
+            static_groups: self.tables.static_group_map.len(),
+            hits: self.hits,
+            misses: self.misses,
+        }
+    }
+
+    /// Returns the original IR cell which `cell` was translated from, if it
+    /// was produced by this map.
+    pub fn original_of_cell(
+        &self,
+        cell: &ArcTex<Cell>,
+    ) -> Option<*const orig_ir::Cell> {
+        let key: *const Cell = cell.as_raw();
+        let key = key as usize;
+        self.tables
+            .cell_origins
+            .get(&key)
+            .map(|x| x as *const orig_ir::Cell)
+    }
+
+    /// Returns the original IR port which `port` was translated from, if it
+    /// was produced by this map.
+    pub fn original_of_port(
+        &self,
+        port: &ArcTex<Port>,
+    ) -> Option<*const orig_ir::Port> {
+        let key: *const Port = port.as_raw();
+        let key = key as usize;
+        self.tables
+            .port_origins
+            .get(&key)
+            .map(|x| x as *const orig_ir::Port)
+    }
+
     pub fn get_port(&mut self, target: &RRC<orig_ir::Port>) -> ArcTex<Port> {
-        let key = target.as_raw();
-        if let Some(x) = self.port_map.get(&key) {
-            x.clone()
+        let key: *const orig_ir::Port = target.as_raw();
+        let key = key as usize;
+        if let Some(x) = self.tables.port_map.get(&key) {
+            self.hits += 1;
+            x
         } else {
+            self.misses += 1;
             let v = arctex(Port::from_ir(target, self));
-            self.port_map.insert(key, v.clone());
+            let (v, fresh) = self.tables.port_map.get_or_insert(key, v);
+            if fresh {
+                self.tables
+                    .port_origins
+                    .get_or_insert(v.as_raw() as usize, key);
+                if let Some(log) = &mut self.fork_log {
+                    log.ports.push(v.clone());
+                }
+            }
             v
         }
     }
 
     pub fn get_cell(&mut self, target: &RRC<orig_ir::Cell>) -> ArcTex<Cell> {
-        let key = target.as_raw();
-        if let Some(x) = self.cell_map.get(&key) {
-            x.clone()
+        let key: *const orig_ir::Cell = target.as_raw();
+        let key = key as usize;
+        if let Some(x) = self.tables.cell_map.get(&key) {
+            self.hits += 1;
+            x
         } else {
+            self.misses += 1;
             let v = arctex(Cell::from_ir_partial(target, self));
-            self.cell_map.insert(key, v.clone());
-            v.write().ports.extend(
-                target.borrow().ports().iter().map(|x| self.get_port(x)),
-            );
+            let (v, fresh) = self.tables.cell_map.get_or_insert(key, v);
+            if fresh {
+                self.tables
+                    .cell_origins
+                    .get_or_insert(v.as_raw() as usize, key);
+                if let Some(log) = &mut self.fork_log {
+                    log.cells.push(v.clone());
+                }
+                v.write().ports.extend(
+                    target.borrow().ports().iter().map(|x| self.get_port(x)),
+                );
+            }
             v
         }
     }
 
     pub fn get_group(&mut self, target: &RRC<orig_ir::Group>) -> ArcTex<Group> {
-        let key = target.as_raw();
-        if let Some(x) = self.group_map.get(&key) {
-            x.clone()
+        let key: *const orig_ir::Group = target.as_raw();
+        let key = key as usize;
+        if let Some(x) = self.tables.group_map.get(&key) {
+            self.hits += 1;
+            x
         } else {
+            self.misses += 1;
             let v = arctex(Group::from_ir_partial(target, self));
-            self.group_map.insert(key, v.clone());
-            v.write()
-                .holes
-                .extend(target.borrow().holes.iter().map(|x| self.get_port(x)));
-            v.write().assignments.extend(
-                target
-                    .borrow()
-                    .assignments
-                    .iter()
-                    .map(|x| Assignment::from_ir(x, self)),
-            );
+            let (v, fresh) = self.tables.group_map.get_or_insert(key, v);
+            if fresh {
+                v.write().holes.extend(
+                    target.borrow().holes.iter().map(|x| self.get_port(x)),
+                );
+                v.write().assignments.extend(
+                    target
+                        .borrow()
+                        .assignments
+                        .iter()
+                        .map(|x| Assignment::from_ir(x, self)),
+                );
+            }
+            v
+        }
+    }
+
+    pub fn get_static_group(
+        &mut self,
+        target: &RRC<orig_ir::StaticGroup>,
+    ) -> ArcTex<StaticGroup> {
+        let key: *const orig_ir::StaticGroup = target.as_raw();
+        let key = key as usize;
+        if let Some(x) = self.tables.static_group_map.get(&key) {
+            self.hits += 1;
+            x
+        } else {
+            self.misses += 1;
+            let v = arctex(StaticGroup::from_ir_partial(target, self));
+            let (v, fresh) = self.tables.static_group_map.get_or_insert(key, v);
+            if fresh {
+                v.write().holes.extend(
+                    target.borrow().holes.iter().map(|x| self.get_port(x)),
+                );
+                v.write().assignments.extend(
+                    target
+                        .borrow()
+                        .assignments
+                        .iter()
+                        .map(|x| Assignment::from_ir(x, self)),
+                );
+            }
             v
         }
     }
@@ -65,22 +280,217 @@ impl TranslationMap {
         &mut self,
         target: &RRC<orig_ir::CombGroup>,
     ) -> ArcTex<CombGroup> {
-        let key = target.as_raw();
-        if let Some(x) = self.comb_group_map.get(&key) {
-            x.clone()
+        let key: *const orig_ir::CombGroup = target.as_raw();
+        let key = key as usize;
+        if let Some(x) = self.tables.comb_group_map.get(&key) {
+            self.hits += 1;
+            x
         } else {
+            self.misses += 1;
             let v = arctex(CombGroup::from_ir(target, self));
-            self.comb_group_map.insert(key, v.clone());
-            v
+            self.tables.comb_group_map.get_or_insert(key, v).0
+        }
+    }
+
+    /// Returns a shared handle to the given guard. Guards which are
+    /// structurally identical (i.e. read the same ports in the same way) are
+    /// only allocated once.
+    pub fn intern_guard<T: ToString + Send + Sync + 'static>(
+        &self,
+        guard: Guard<T>,
+    ) -> Arc<Guard<T>> {
+        self.tables.guards.intern(guard)
+    }
+
+    /// Translates every component in `comps` with this map, in order.
+    pub fn translate_components(
+        &mut self,
+        comps: &[orig_ir::Component],
+    ) -> Vec<Component> {
+        comps.iter().map(|x| Component::from_ir(x, self)).collect()
+    }
+
+    /// Translates every component in `comps` like
+    /// [TranslationMap::translate_components], but each on its own fork of
+    /// this map on the rayon thread pool. The forks share the tables of this
+    /// map, so every structure is still translated once and identical guards
+    /// are still interned together, and the ids of cells and ports are the
+    /// same as those of a sequential translation.
+    pub fn translate_components_parallel(
+        &mut self,
+        comps: &[orig_ir::Component],
+    ) -> Vec<Component> {
+        let jobs = comps
+            .iter()
+            .map(|comp| (SourceComponent(comp), self.fork()))
+            .collect::<Vec<_>>();
+
+        let done = jobs
+            .into_par_iter()
+            .map(|(comp, mut fork)| {
+                let translated = Component::from_ir(comp.0, &mut fork);
+                (translated, fork)
+            })
+            .collect::<Vec<_>>();
+
+        done.into_iter()
+            .map(|(translated, fork)| {
+                self.join(fork);
+                translated
+            })
+            .collect()
+    }
+
+    /// A map sharing the tables of this one, which numbers the cells and
+    /// ports it translates from zero
+    fn fork(&self) -> Self {
+        Self {
+            tables: Arc::clone(&self.tables),
+            fork_log: Some(ForkLog::default()),
+            drop_attributes: self.drop_attributes,
+            ..Default::default()
         }
     }
 
+    /// Renumbers the cells and ports translated by `fork` to follow those
+    /// already translated by this map, and adds its lookups to the stats
+    fn join(&mut self, fork: TranslationMap) {
+        let log = fork.fork_log.expect("only forks are joined");
+        for cell in log.cells {
+            cell.write().uid += self.next_uid;
+        }
+        for port in log.ports {
+            port.write().uid += self.next_uid;
+        }
+        self.next_uid += fork.next_uid;
+        self.hits += fork.hits;
+        self.misses += fork.misses;
+    }
+
     /// A convenience method that just invokes the assignment constructor with
     /// the translator
-    pub fn get_assignment<T: Clone>(
+    pub fn get_assignment<T: Clone + ToString + Send + Sync + 'static>(
         &mut self,
         target: &orig_ir::Assignment<T>,
     ) -> Assignment<T> {
         Assignment::from_ir(target, self)
     }
 }
+
+/// A hash map split into [SHARDS] independently locked parts, so that threads
+/// working on different keys rarely wait on one another
+#[derive(Debug)]
+struct ShardedMap<K, V> {
+    shards: [RwLock<HashMap<K, V>>; SHARDS],
+}
+
+impl<K, V> Default for ShardedMap<K, V> {
+    fn default() -> Self {
+        Self {
+            shards: std::array::from_fn(|_| Default::default()),
+        }
+    }
+}
+
+impl<K: Hash + Eq, V> ShardedMap<K, V> {
+    fn shard(&self, key: &K) -> &RwLock<HashMap<K, V>> {
+        let mut hasher = DefaultHasher::new();
+        key.hash(&mut hasher);
+        &self.shards[hasher.finish() as usize % SHARDS]
+    }
+
+    fn len(&self) -> usize {
+        self.shards.iter().map(|x| x.read().len()).sum()
+    }
+}
+
+impl<K: Hash + Eq, V: Clone> ShardedMap<K, V> {
+    fn get(&self, key: &K) -> Option<V> {
+        self.shard(key).read().get(key).cloned()
+    }
+
+    /// Inserts `value` unless `key` is already present. Returns the value
+    /// stored under `key` and whether it is the given one.
+    fn get_or_insert(&self, key: K, value: V) -> (V, bool) {
+        match self.shard(&key).write().entry(key) {
+            Entry::Occupied(x) => (x.get().clone(), false),
+            Entry::Vacant(x) => (x.insert(value).clone(), true),
+        }
+    }
+}
+
+/// Interned guards, keyed by the type `T` of the guard and its shape. The
+/// values are `Arc<Guard<T>>` for the `T` recorded in the key.
+#[derive(Debug, Default)]
+struct GuardInterner(
+    ShardedMap<(TypeId, GuardKey), Box<dyn Any + Send + Sync>>,
+);
+
+impl GuardInterner {
+    fn intern<T: ToString + Send + Sync + 'static>(
+        &self,
+        guard: Guard<T>,
+    ) -> Arc<Guard<T>> {
+        let key = (TypeId::of::<T>(), GuardKey::new(&guard));
+        let shard = self.0.shard(&key);
+        if let Some(existing) = shard
+            .read()
+            .get(&key)
+            .and_then(|x| x.downcast_ref::<Arc<Guard<T>>>())
+        {
+            return existing.clone();
+        }
+
+        // another thread may have interned the same guard since the read
+        shard
+            .write()
+            .entry(key)
+            .or_insert_with(|| Box::new(Arc::new(guard)))
+            .downcast_ref::<Arc<Guard<T>>>()
+            .expect("guards are keyed by their type")
+            .clone()
+    }
+}
+
+/// A hashable stand-in for a translated guard. Ports are compared by identity,
+/// i.e. by address, rather than by name so guards from different components
+/// are never conflated.
+#[derive(Debug, Hash, PartialEq, Eq)]
+enum GuardKey {
+    Or(Box<GuardKey>, Box<GuardKey>),
+    And(Box<GuardKey>, Box<GuardKey>),
+    Not(Box<GuardKey>),
+    True,
+    False,
+    CompOp(Discriminant<PortComp>, usize, usize),
+    Port(usize),
+    Info(String),
+}
+
+impl GuardKey {
+    fn new<T: ToString>(guard: &Guard<T>) -> Self {
+        match guard {
+            Guard::Or(l, r) => {
+                GuardKey::Or(Self::new(l).into(), Self::new(r).into())
+            }
+            Guard::And(l, r) => {
+                GuardKey::And(Self::new(l).into(), Self::new(r).into())
+            }
+            Guard::Not(g) => GuardKey::Not(Self::new(g).into()),
+            Guard::True => GuardKey::True,
+            Guard::False => GuardKey::False,
+            Guard::CompOp(op, l, r) => GuardKey::CompOp(
+                std::mem::discriminant(op),
+                port_address(l),
+                port_address(r),
+            ),
+            Guard::Port(p) => GuardKey::Port(port_address(p)),
+            Guard::Info(i) => GuardKey::Info(i.to_string()),
+        }
+    }
+}
+
+fn port_address(port: &ArcTex<Port>) -> usize {
+    let ptr: *const Port = port.as_raw();
+    ptr as usize
+}
@@ -0,0 +1,289 @@
+use std::collections::{HashMap, HashSet};
+
+use calyx_frontend::{BoolAttr, NumAttr};
+use calyx_ir::{Canonical, CellType, Direction, Nothing};
+use calyx_utils::Id;
+
+use crate::errors::{InterpreterError, InterpreterResult};
+use crate::utils::{ArcTex, AsRaw};
+
+use super::{
+    comb_graph::CombGraph, Assignment, Cell, Component, Control, Guard, Port,
+    StaticControl,
+};
+
+/// A potential problem found while validating a [Component]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ValidationWarning {
+    /// An assignment whose source and destination have different widths
+    WidthMismatch {
+        dst: Canonical,
+        dst_width: u64,
+        src: Canonical,
+        src_width: u64,
+    },
+    /// A port with more than one unconditional driver in the same scope, as
+    /// found by [Component::check_single_driver]. The scope is the name of
+    /// the group or comb group, whose drivers include the continuous
+    /// assignments, or `None` for the continuous assignments alone
+    MultipleDrivers { port: Canonical, scope: Option<Id> },
+    /// An input port of a cell which is never driven by an assignment or
+    /// invoke
+    UndrivenInput { port: Canonical },
+    /// A cycle in the combinational assignments. The port lies on the cycle
+    CombinationalCycle { port: Canonical },
+}
+
+impl std::fmt::Display for ValidationWarning {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ValidationWarning::WidthMismatch {
+                dst,
+                dst_width,
+                src,
+                src_width,
+            } => write!(
+                f,
+                "assignment {dst} = {src} has mismatched widths ({dst_width} and {src_width})"
+            ),
+            ValidationWarning::MultipleDrivers { port, scope } => match scope {
+                Some(scope) => write!(
+                    f,
+                    "{port} has multiple unconditional drivers in {scope}"
+                ),
+                None => write!(
+                    f,
+                    "{port} has multiple unconditional continuous drivers"
+                ),
+            },
+            ValidationWarning::UndrivenInput { port } => {
+                write!(f, "input port {port} is never driven")
+            }
+            ValidationWarning::CombinationalCycle { port } => {
+                write!(f, "{port} is part of a combinational cycle")
+            }
+        }
+    }
+}
+
+/// Runs every structural check on `comp` and collects the results
+pub(super) fn validate(comp: &Component) -> Vec<ValidationWarning> {
+    let mut warnings = vec![];
+
+    for assign in comp.all_assignments() {
+        check_width(&assign, &mut warnings);
+    }
+
+    find_driver_conflicts(comp, |scope, conflicts| {
+        let mut ports = conflicts
+            .iter()
+            .map(|(_, assign)| assign.dst.read().canonical())
+            .collect::<Vec<_>>();
+        ports.sort();
+        ports.dedup();
+        warnings.extend(
+            ports
+                .into_iter()
+                .map(|port| ValidationWarning::MultipleDrivers { port, scope }),
+        );
+    });
+
+    check_undriven(comp, &mut warnings);
+
+    if let Some(port) = CombGraph::from_component(comp).find_cycle() {
+        warnings.push(ValidationWarning::CombinationalCycle {
+            port: port.read().canonical(),
+        });
+    }
+
+    warnings
+}
+
+/// Errors on the first conflict found by [find_driver_conflicts]
+pub(super) fn check_single_driver(comp: &Component) -> InterpreterResult<()> {
+    let mut result = Ok(());
+    find_driver_conflicts(comp, |_, conflicts| {
+        let Some((prev, assign)) = conflicts.first() else {
+            return;
+        };
+        if result.is_ok() {
+            let port = assign.dst.read();
+            result = Err(InterpreterError::conflicting_assignments(
+                port.name,
+                port.get_parent_name(),
+                prev,
+                assign,
+            )
+            .into());
+        }
+    });
+    result
+}
+
+/// A pair of assignments driving the same port, in the order they were found
+type Conflict<'a> = (&'a Assignment<Nothing>, &'a Assignment<Nothing>);
+
+/// Finds the ports with two drivers whose guards simplify to true and which
+/// may be active together. The continuous assignments are checked alone,
+/// with scope `None`, and then alongside each group and comb group in turn.
+/// `on_scope` is called once per scope with the conflicts found there, which
+/// only include a continuous assignment pair in the `None` scope.
+fn find_driver_conflicts<F>(comp: &Component, mut on_scope: F)
+where
+    F: FnMut(Option<Id>, &[Conflict]),
+{
+    let continuous = &comp.continuous_assignments;
+    on_scope(None, &scope_conflicts(&[], continuous));
+    for group in comp.groups.iter() {
+        let group = group.read();
+        let conflicts = scope_conflicts(continuous, &group.assignments);
+        on_scope(Some(group.name()), &conflicts);
+    }
+    for comb_group in comp.comb_groups.iter() {
+        let comb_group = comb_group.read();
+        let conflicts = scope_conflicts(continuous, &comb_group.assignments);
+        on_scope(Some(comb_group.name()), &conflicts);
+    }
+}
+
+/// Compares the unconditional drivers in `scoped` with each other and with
+/// those in `continuous`, but not the ones in `continuous` with each other
+fn scope_conflicts<'a>(
+    continuous: &'a [Assignment<Nothing>],
+    scoped: &'a [Assignment<Nothing>],
+) -> Vec<Conflict<'a>> {
+    let unconditional =
+        |a: &&Assignment<Nothing>| matches!(a.guard.simplified(), Guard::True);
+    let mut drivers: HashMap<*const Port, &Assignment<Nothing>> = continuous
+        .iter()
+        .filter(unconditional)
+        .map(|a| (a.dst.as_raw(), a))
+        .collect();
+    let mut conflicts = vec![];
+    for assign in scoped.iter().filter(unconditional) {
+        if let Some(prev) = drivers.insert(assign.dst.as_raw(), assign) {
+            conflicts.push((prev, assign));
+        }
+    }
+    conflicts
+}
+
+fn check_width(
+    assign: &Assignment<Nothing>,
+    warnings: &mut Vec<ValidationWarning>,
+) {
+    let dst = assign.dst.read();
+    let src = assign.src.read();
+    if dst.width != src.width {
+        warnings.push(ValidationWarning::WidthMismatch {
+            dst: dst.canonical(),
+            dst_width: dst.width,
+            src: src.canonical(),
+            src_width: src.width,
+        })
+    }
+}
+
+fn check_undriven(comp: &Component, warnings: &mut Vec<ValidationWarning>) {
+    let mut driven: HashSet<*const Port> =
+        comp.all_assignments().map(|a| a.dst.as_raw()).collect();
+    collect_invoke_inputs(&comp.control, &mut driven);
+
+    let mut undriven = vec![];
+    for cell in comp.cells.iter() {
+        let cell = cell.read();
+        if !matches!(
+            cell.prototype,
+            CellType::Primitive { .. } | CellType::Component { .. }
+        ) {
+            continue;
+        }
+
+        for port in cell.ports.iter() {
+            let key: *const Port = port.as_raw();
+            let port = port.read();
+            // the clock and reset signals are driven implicitly
+            if port.direction == Direction::Input
+                && !port.attributes.has(BoolAttr::Clk)
+                && !port.attributes.has(BoolAttr::Reset)
+                && !driven.contains(&key)
+            {
+                undriven.push(port.canonical());
+            }
+        }
+    }
+    undriven.sort();
+
+    warnings.extend(
+        undriven
+            .into_iter()
+            .map(|port| ValidationWarning::UndrivenInput { port }),
+    );
+}
+
+/// Marks the ports of invoked cells which are driven by the invoke
+fn collect_invoke_inputs(control: &Control, driven: &mut HashSet<*const Port>) {
+    match control {
+        Control::Seq(s) => s
+            .stmts
+            .iter()
+            .for_each(|c| collect_invoke_inputs(c, driven)),
+        Control::Par(p) => p
+            .stmts
+            .iter()
+            .for_each(|c| collect_invoke_inputs(c, driven)),
+        Control::If(i) => {
+            collect_invoke_inputs(&i.tbranch, driven);
+            collect_invoke_inputs(&i.fbranch, driven);
+        }
+        Control::While(w) => collect_invoke_inputs(&w.body, driven),
+        Control::Repeat(r) => collect_invoke_inputs(&r.body, driven),
+        Control::Invoke(inv) => mark_invoked(&inv.comp, &inv.inputs, driven),
+        Control::Static(s) => collect_static_invoke_inputs(s, driven),
+        Control::Enable(_) | Control::Empty(_) => {}
+    }
+}
+
+fn collect_static_invoke_inputs(
+    control: &StaticControl,
+    driven: &mut HashSet<*const Port>,
+) {
+    match control {
+        StaticControl::Seq(s) => s
+            .stmts
+            .iter()
+            .for_each(|c| collect_static_invoke_inputs(c, driven)),
+        StaticControl::Par(p) => p
+            .stmts
+            .iter()
+            .for_each(|c| collect_static_invoke_inputs(c, driven)),
+        StaticControl::If(i) => {
+            collect_static_invoke_inputs(&i.tbranch, driven);
+            collect_static_invoke_inputs(&i.fbranch, driven);
+        }
+        StaticControl::Repeat(r) => {
+            collect_static_invoke_inputs(&r.body, driven)
+        }
+        StaticControl::Invoke(inv) => {
+            mark_invoked(&inv.comp, &inv.inputs, driven)
+        }
+        StaticControl::Enable(_) | StaticControl::Empty(_) => {}
+    }
+}
+
+/// Marks the `go` port and the given inputs of an invoked cell as driven
+fn mark_invoked(
+    comp: &ArcTex<Cell>,
+    inputs: &[(Id, ArcTex<Port>)],
+    driven: &mut HashSet<*const Port>,
+) {
+    let comp = comp.read();
+    for port in comp.ports.iter() {
+        let key: *const Port = port.as_raw();
+        let port = port.read();
+        let is_input = inputs.iter().any(|(name, _)| *name == port.name);
+        if is_input || port.attributes.has(NumAttr::Go) {
+            driven.insert(key);
+        }
+    }
+}
@@ -1,6 +1,6 @@
 pub mod interpreter;
 pub mod primitives;
-pub use utils::MemoryMap;
+pub use utils::{MemoryDiff, MemoryLayout, MemoryMap, StimulusMap};
 pub mod configuration;
 pub mod debugger;
 pub mod errors;
@@ -9,11 +9,12 @@ use interp::{
     environment::InterpreterState,
     errors::{InterpreterError, InterpreterResult},
     interpreter::ComponentInterpreter,
-    interpreter_ir::{self as iir, Component, TranslationMap},
+    interpreter_ir::{self as iir, TranslationMap},
 };
 use rustyline::error::ReadlineError;
 use slog::warn;
 use std::{
+    num::NonZeroU64,
     path::{Path, PathBuf},
     sync::Arc,
 };
@@ -63,6 +64,21 @@ pub struct Opts {
     #[argh(switch, short = 'q', long = "--quiet")]
     quiet: bool,
 
+    /// the maximum number of iterations combinational convergence may take
+    /// before erroring, at least one. Unbounded by default
+    #[argh(option, long = "max-settle-iterations")]
+    max_settle_iterations: Option<NonZeroU64>,
+
+    /// the maximum number of cycles to run the program for before stopping
+    /// early. Unbounded by default
+    #[argh(option, long = "max-cycles")]
+    max_cycles: Option<u64>,
+
+    /// the name of the component to interpret. Defaults to the program's
+    /// entrypoint
+    #[argh(option, long = "entry")]
+    entry: Option<String>,
+
     /// serializes values using base64 encoding
     #[argh(switch, long = "raw")]
     raw: bool,
@@ -117,6 +133,10 @@ fn print_res(
                 println!("Exiting.");
                 Ok(())
             }
+            InterpreterError::CycleBudgetExceeded { ref state, .. } => {
+                println!("{}", serde_json::to_string_pretty(state).unwrap());
+                Err(e)
+            }
             _ => Err(e),
         },
     }
@@ -133,6 +153,9 @@ fn main() -> InterpreterResult<()> {
         .allow_invalid_memory_access(opts.allow_invalid_memory_access)
         .error_on_overflow(opts.error_on_overflow)
         .allow_par_conflicts(opts.allow_par_conflicts)
+        .max_settle_iterations(opts.max_settle_iterations)
+        .max_cycles(opts.max_cycles)
+        .entry_component(opts.entry.as_deref().map(ir::Id::from))
         .build();
 
     interp::logging::initialze_logger(config.quiet);
@@ -162,23 +185,33 @@ fn main() -> InterpreterResult<()> {
         todo!("The flat interpreter cannot yet interpret programs")
     }
 
-    let entry_point = ctx.entrypoint;
+    let entry_point = config.entry_component.unwrap_or(ctx.entrypoint);
 
     let metadata = ctx.metadata;
 
     let mut transformer = TranslationMap::new();
 
     let components: iir::ComponentCtx = Arc::new(
-        ctx.components
-            .iter()
-            .map(|x| Arc::new(Component::from_ir(x, &mut transformer)))
+        transformer
+            .translate_components_parallel(&ctx.components)
+            .into_iter()
+            .map(Arc::new)
             .collect(),
     );
 
+    for comp in components.iter() {
+        comp.check_ref_cells(&components)?;
+    }
+
+    let missing_entry = match config.entry_component {
+        Some(name) => InterpreterError::UnknownComponent(name.to_string()),
+        None => InterpreterError::MissingMainComponent,
+    };
+
     let main_component = components
         .iter()
         .find(|&cm| cm.name == entry_point)
-        .ok_or(InterpreterError::MissingMainComponent)?;
+        .ok_or(missing_entry)?;
 
     let mut mems = interp::MemoryMap::inflate_map(&opts.data_file)?;
 
@@ -1,6 +1,6 @@
 use crate::{
     errors::InterpreterResult,
-    interpreter::ComponentInterpreter,
+    interpreter::{format_value, ComponentInterpreter},
     structures::state_views::{FullySerialize, StateView},
     utils::PrintCode,
     values::Value,
@@ -134,6 +134,7 @@ pub enum Entry {
     I(i64),
     Frac(Fraction),
     Value(Value),
+    Hex(String),
 }
 
 impl From<u64> for Entry {
@@ -162,6 +163,7 @@ impl Entry {
             PrintCode::UFixed(f) => val.as_ufp(*f).into(),
             PrintCode::SFixed(f) => val.as_sfp(*f).into(),
             PrintCode::Binary => Entry::Value(val.clone()),
+            PrintCode::Hex => Entry::Hex(format_value(val, *code)),
         }
     }
 }
@@ -173,6 +175,7 @@ impl Display for Entry {
             Entry::I(v) => write!(f, "{}", v),
             Entry::Frac(v) => write!(f, "{}", v),
             Entry::Value(v) => write!(f, "{}", v),
+            Entry::Hex(v) => write!(f, "{}", v),
         }
     }
 }
@@ -54,6 +54,41 @@ impl StdReg {
             .1;
         Self::from_constants(width, name)
     }
+
+    /// Constructs a register holding the given initial value. The
+    /// initialization data must contain exactly one entry, which must fit in
+    /// the register's `WIDTH`.
+    pub fn from_initial_value(
+        params: &ir::Binding,
+        name: ir::Id,
+        initial: Vec<Value>,
+    ) -> InterpreterResult<Self> {
+        let mut reg = Self::new(params, name);
+
+        if initial.len() != 1 {
+            return Err(InterpreterError::IncorrectMemorySize {
+                mem_dim: String::from("Register"),
+                expected: 1,
+                given: initial.len(),
+            }
+            .into());
+        }
+
+        if !Value::unsigned_value_fits_in(
+            initial[0].bv_ref(),
+            reg.width as usize,
+        ) {
+            return Err(InterpreterError::MemoryInitOverflow {
+                cell: reg.full_name,
+                index: 0,
+                width: reg.width,
+            }
+            .into());
+        }
+
+        reg.data[0] = Value::from(initial[0].as_unsigned(), reg.width);
+        Ok(reg)
+    }
 }
 
 impl Named for StdReg {
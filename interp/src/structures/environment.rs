@@ -15,11 +15,11 @@ use crate::{
     interpreter_ir::Cell,
 };
 use crate::{
-    interpreter::{ComponentInterpreter, Interpreter},
+    interpreter::{ComponentInterpreter, Interpreter, InterpreterObserver},
     interpreter_ir::Guard,
 };
 use crate::{
-    utils::{AsRaw, MemoryMap},
+    utils::{AsRaw, MemoryMap, StimulusMap},
     values::Value,
 };
 use calyx_ir::{self as ir};
@@ -27,6 +27,7 @@ use ir::{Nothing, PortComp};
 
 use std::{
     collections::{HashMap, HashSet},
+    num::NonZeroU64,
     sync::Arc,
 };
 use std::{fmt::Debug, iter::once};
@@ -74,6 +75,19 @@ pub struct InterpreterState {
     /// flag which tells the environment to allow certain par conflicts on
     /// merging
     allow_par_conflicts: bool,
+
+    /// the maximum number of iterations combinational convergence may take,
+    /// if bounded
+    max_settle_iterations: Option<NonZeroU64>,
+
+    /// the maximum number of cycles the program may run for, if bounded
+    max_cycles: Option<u64>,
+
+    /// values to drive onto the top-level inputs on each cycle, if any
+    stimulus: Option<Arc<StimulusMap>>,
+
+    /// an observer to notify of interpreter events, if one is registered
+    observer: Option<Arc<dyn InterpreterObserver>>,
 }
 
 // this is really bad
@@ -89,6 +103,10 @@ impl Debug for InterpreterState {
             .field("component", &self.component)
             .field("sub_comp_set", &self.sub_comp_set)
             .field("allow_par_conflicts", &self.allow_par_conflicts)
+            .field("max_settle_iterations", &self.max_settle_iterations)
+            .field("max_cycles", &self.max_cycles)
+            .field("stimulus", &self.stimulus)
+            .field("observer", &self.observer)
             .finish()
     }
 }
@@ -106,17 +124,24 @@ impl InterpreterState {
         // only for the main component
         let qin =
             ComponentQualifiedInstanceName::new_single(target, target.name);
+        // the port map must be built first since constructing the cells
+        // consumes the initialization data
+        let port_map = InterpreterState::construct_port_map(target, mems)?;
         let (map, set) =
             Self::construct_cell_map(target, ctx, mems, &qin, configs)?;
 
         Ok(Self {
             context: Arc::clone(ctx),
             clk: 0,
-            port_map: InterpreterState::construct_port_map(target),
+            port_map,
             cell_map: map,
             component: target.clone(),
             sub_comp_set: Arc::new(set),
             allow_par_conflicts: configs.allow_par_conflicts,
+            max_settle_iterations: configs.max_settle_iterations,
+            max_cycles: configs.max_cycles,
+            stimulus: configs.stimulus.clone(),
+            observer: configs.observer.clone(),
         })
     }
 
@@ -131,20 +156,45 @@ impl InterpreterState {
         qin: &ComponentQualifiedInstanceName,
         configs: &Config,
     ) -> InterpreterResult<Self> {
+        let port_map = InterpreterState::construct_port_map(target, mems)?;
         let (map, set) =
             Self::construct_cell_map(target, ctx, mems, qin, configs)?;
 
         Ok(Self {
             context: Arc::clone(ctx),
             clk: 0,
-            port_map: InterpreterState::construct_port_map(target),
+            port_map,
             cell_map: map,
             component: target.clone(),
             sub_comp_set: Arc::new(set),
             allow_par_conflicts: configs.allow_par_conflicts,
+            max_settle_iterations: configs.max_settle_iterations,
+            max_cycles: configs.max_cycles,
+            stimulus: configs.stimulus.clone(),
+            observer: configs.observer.clone(),
         })
     }
 
+    /// The bound on combinational convergence iterations, if any
+    pub fn max_settle_iterations(&self) -> Option<NonZeroU64> {
+        self.max_settle_iterations
+    }
+
+    /// The cycle budget for the program, if any
+    pub fn max_cycles(&self) -> Option<u64> {
+        self.max_cycles
+    }
+
+    /// The stimulus for the top-level inputs, if any
+    pub fn stimulus(&self) -> Option<&Arc<StimulusMap>> {
+        self.stimulus.as_ref()
+    }
+
+    /// The registered observer, if any
+    pub fn observer(&self) -> Option<&Arc<dyn InterpreterObserver>> {
+        self.observer.as_ref()
+    }
+
     /// Insert a new value for the given constant port into the environment
     pub fn insert<P: AsRaw<iir::Port>>(&mut self, port: P, value: Value) {
         self.port_map.set(port.as_raw(), value);
@@ -284,7 +334,20 @@ impl InterpreterState {
             }
             "std_pad" => Box::new(combinational::StdPad::new(params, cell_qin)),
             // State components
-            "std_reg" => Box::new(stateful::mem::StdReg::new(params, cell_qin)),
+            "std_reg" => {
+                let init = mems.as_mut().and_then(|x| x.remove(&cell_name));
+
+                match init {
+                    Some(vals) => {
+                        Box::new(stateful::mem::StdReg::from_initial_value(
+                            params, cell_qin, vals,
+                        )?)
+                    }
+                    None => {
+                        Box::new(stateful::mem::StdReg::new(params, cell_qin))
+                    }
+                }
+            }
             "std_mem_d1" => {
                 let init = mems.as_mut().and_then(|x| x.remove(&cell_name));
 
@@ -520,8 +583,13 @@ impl InterpreterState {
 
     /// A helper meathod which constructs the initial environment map from ports
     /// to values and provides the appropriate default values for ports
-    /// depending on their parent cell.
-    fn construct_port_map(comp: &iir::Component) -> PortValMap {
+    /// depending on their parent cell. Registers with an entry in the
+    /// initialization data start with that value on their output, and it is
+    /// an error for that value not to fit in the register.
+    fn construct_port_map(
+        comp: &iir::Component,
+        mems: &Option<MemoryMap>,
+    ) -> InterpreterResult<PortValMap> {
         let mut map = HashMap::new();
 
         for port in comp.signature.read().ports.iter() {
@@ -546,16 +614,42 @@ impl InterpreterState {
                         map.insert(pt as ConstPort, Value::from(*val, *width));
                     }
                 }
-                ir::CellType::Primitive { .. } => {
+                ir::CellType::Primitive { name, .. } => {
+                    let seed = mems
+                        .as_ref()
+                        .filter(|_| *name == "std_reg")
+                        .and_then(|m| m.get(&cll.name()))
+                        .filter(|vals| vals.len() == 1)
+                        .map(|vals| &vals[0]);
+
+                    if let Some(seed) = seed {
+                        let width =
+                            cll.get_parameter("WIDTH").unwrap_or_default();
+                        if !Value::unsigned_value_fits_in(
+                            seed.bv_ref(),
+                            width as usize,
+                        ) {
+                            return Err(InterpreterError::MemoryInitOverflow {
+                                cell: cll.name(),
+                                index: 0,
+                                width,
+                            }
+                            .into());
+                        }
+                    }
+
                     for port in &cll.ports {
                         let pt: &iir::Port = &port.read();
-                        map.insert(
-                            pt as ConstPort,
-                            Value::from(
+                        let val = match &seed {
+                            Some(seed) if pt.name == "out" => {
+                                Value::from(seed.as_unsigned(), pt.width)
+                            }
+                            _ => Value::from(
                                 cll.get_parameter("VALUE").unwrap_or_default(),
                                 pt.width,
                             ),
-                        );
+                        };
+                        map.insert(pt as ConstPort, val);
                     }
                 }
                 ir::CellType::Component { .. } => {
@@ -571,7 +665,7 @@ impl InterpreterState {
             }
         }
 
-        map.into()
+        Ok(map.into())
     }
 
     /// Return the value associated with a component's port.
@@ -620,6 +714,10 @@ impl InterpreterState {
             component: self.component.clone(),
             sub_comp_set: Arc::clone(&self.sub_comp_set),
             allow_par_conflicts: self.allow_par_conflicts,
+            max_settle_iterations: self.max_settle_iterations,
+            max_cycles: self.max_cycles,
+            stimulus: self.stimulus.clone(),
+            observer: self.observer.clone(),
         }
     }
     /// Creates a fork of the source environment which has the same clock and
@@ -635,6 +733,10 @@ impl InterpreterState {
             component: self.component.clone(),
             sub_comp_set: Arc::clone(&self.sub_comp_set),
             allow_par_conflicts: self.allow_par_conflicts,
+            max_settle_iterations: self.max_settle_iterations,
+            max_cycles: self.max_cycles,
+            stimulus: self.stimulus.clone(),
+            observer: self.observer.clone(),
         }
     }
 
@@ -718,6 +820,7 @@ impl InterpreterState {
                 }
             }
             Guard::True => true,
+            Guard::False => false,
             Guard::Info(_) => panic!("unimplemented"),
         })
     }
@@ -8,6 +8,9 @@ use itertools::Itertools;
 use serde::de::{self, Deserialize, Visitor};
 use serde::Serialize;
 
+use crate::debugger::PrintCode;
+use crate::errors::{InterpreterError, InterpreterResult};
+
 pub type BitString = BitVec<usize, Lsb0>;
 
 /// Retrieves the unsigned fixed point representation of `v`. This splits the representation into
@@ -269,6 +272,23 @@ impl From<BitString> for Value {
     }
 }
 
+/// Collects bits, least significant first, into a Value whose width is the
+/// number of bits given. This is the inverse of [Value::bits].
+///
+/// # Example
+/// ```
+/// use interp::values::*;
+/// let val_6_4: Value = [false, true, true, false].into_iter().collect();
+/// assert_eq!(val_6_4, Value::from(6, 4));
+/// ```
+impl FromIterator<bool> for Value {
+    fn from_iter<I: IntoIterator<Item = bool>>(bits: I) -> Self {
+        Self {
+            vec: bits.into_iter().collect(),
+        }
+    }
+}
+
 impl Value {
     pub fn unsigned_value_fits_in(vec: &BitString, width: usize) -> bool {
         vec.len() <= width // obviously fits then
@@ -289,10 +309,26 @@ impl Value {
         self.vec.len() as u64
     }
 
+    /// Returns true if both values have the same width and the same bits.
+    /// Values of differing widths are never equal, even if they represent
+    /// the same number. This is the comparison used by `==`.
+    pub fn bitwise_eq(&self, other: &Value) -> bool {
+        self.vec.len() == other.vec.len() && *self.vec == *other.vec
+    }
+
     pub fn iter(&self) -> impl DoubleEndedIterator<Item = bool> + '_ {
         self.vec.iter().by_vals()
     }
 
+    /// Iterates over the bits of this value, least significant first,
+    /// yielding exactly [Value::width] of them. Collecting the bits back into
+    /// a Value gives an equal Value.
+    pub fn bits(
+        &self,
+    ) -> impl DoubleEndedIterator<Item = bool> + ExactSizeIterator + '_ {
+        self.vec.iter().by_vals()
+    }
+
     pub fn clone_bit_vec(&self) -> BitString {
         self.vec.clone()
     }
@@ -374,6 +410,53 @@ impl Value {
     pub fn from_bv(bv: BitString) -> Self {
         bv.into()
     }
+
+    /// Creates a Value of the given width from a sequence of bits, least
+    /// significant first. Fewer bits than the width are zero-extended. Errors
+    /// if any bit set at or beyond `width` would be lost.
+    ///
+    /// # Example
+    /// ```
+    /// use interp::values::*;
+    /// let val_6_4 = Value::from_bits(4, &[false, true, true]).unwrap();
+    /// assert_eq!(val_6_4.as_u64(), 6);
+    /// ```
+    pub fn from_bits(width: u64, bits: &[bool]) -> InterpreterResult<Self> {
+        let bv: BitString = bits.iter().copied().collect();
+        Value::from_bv_checked(width, bv)
+    }
+
+    /// Creates a Value of the given width from little-endian bytes. Errors if
+    /// any bit set at or beyond `width` would be lost.
+    ///
+    /// # Example
+    /// ```
+    /// use interp::values::*;
+    /// let val = Value::from_bytes_le(12, &[0xff, 0x0f]).unwrap();
+    /// assert_eq!(val.as_u64(), 0xfff);
+    /// ```
+    pub fn from_bytes_le(width: u64, bytes: &[u8]) -> InterpreterResult<Self> {
+        let bv = BitSlice::<u8, Lsb0>::from_slice(bytes)
+            .iter()
+            .by_vals()
+            .collect();
+        Value::from_bv_checked(width, bv)
+    }
+
+    fn from_bv_checked(
+        width: u64,
+        mut bv: BitString,
+    ) -> InterpreterResult<Self> {
+        if !Value::unsigned_value_fits_in(&bv, width as usize) {
+            return Err(InterpreterError::BitPatternTooWide {
+                width,
+                high_bit: bv.last_one().unwrap_or_default(),
+            }
+            .into());
+        }
+        bv.resize(width as usize, false);
+        Ok(Value { vec: bv })
+    }
     /// Returns a Value truncated to length new_size.
     ///
     /// # Example
@@ -423,6 +506,71 @@ impl Value {
         Value { vec }
     }
 
+    /// Truncates the value to `new_width` bits, keeping the low bits. Errors
+    /// if `new_width` is larger than the current width.
+    ///
+    /// # Example
+    /// ```
+    /// use interp::values::*;
+    /// let v = Value::from(0b1011, 4).try_truncate(2).unwrap();
+    /// assert_eq!(v, Value::from(0b11, 2));
+    /// assert!(Value::from(1, 4).try_truncate(5).is_err());
+    /// ```
+    pub fn try_truncate(&self, new_width: u64) -> InterpreterResult<Value> {
+        self.check_resize("truncate", new_width, new_width <= self.width())?;
+        Ok(self.truncate(new_width as usize))
+    }
+
+    /// Zero-extends the value to `new_width` bits. Errors if `new_width` is
+    /// smaller than the current width.
+    ///
+    /// # Example
+    /// ```
+    /// use interp::values::*;
+    /// let v = Value::from(0b1011, 4).zero_extend(6).unwrap();
+    /// assert_eq!(v, Value::from(0b001011, 6));
+    /// ```
+    pub fn zero_extend(&self, new_width: u64) -> InterpreterResult<Value> {
+        self.check_resize("zero extend", new_width, new_width >= self.width())?;
+        Ok(self.ext(new_width as usize))
+    }
+
+    /// Sign-extends the value to `new_width` bits, treating the most
+    /// significant bit as the sign. Errors if `new_width` is smaller than the
+    /// current width.
+    ///
+    /// # Example
+    /// ```
+    /// use interp::values::*;
+    /// let v = Value::from(0b1011, 4).sign_extend(6).unwrap();
+    /// assert_eq!(v, Value::from(0b111011, 6));
+    /// ```
+    pub fn sign_extend(&self, new_width: u64) -> InterpreterResult<Value> {
+        self.check_resize("sign extend", new_width, new_width >= self.width())?;
+        if self.vec.is_empty() {
+            return Ok(Value::zeroes(new_width as usize));
+        }
+        Ok(self.sext(new_width as usize))
+    }
+
+    fn check_resize(
+        &self,
+        operation: &'static str,
+        new_width: u64,
+        valid: bool,
+    ) -> InterpreterResult<()> {
+        if valid {
+            Ok(())
+        } else {
+            Err(InterpreterError::InvalidResize {
+                operation,
+                width: self.width(),
+                new_width,
+            }
+            .into())
+        }
+    }
+
     /// Converts value into u64 type.
     ///
     /// # Example
@@ -538,6 +686,41 @@ impl Value {
         )
     }
 
+    /// Converts value into i64 type using 2C representation, returning `None`
+    /// rather than panicking if the value does not fit. A zero-width value is
+    /// read as zero.
+    ///
+    /// # Example
+    /// ```
+    /// use interp::values::*;
+    /// assert_eq!(Value::from(-3_i64, 100).try_as_i64(), Some(-3));
+    /// assert_eq!(Value::from(1_u128 << 80, 100).try_as_i64(), None);
+    /// ```
+    pub fn try_as_i64(&self) -> Option<i64> {
+        if self.vec.is_empty() {
+            return Some(0);
+        }
+        // every bit from the sign bit of an i64 upwards must match it
+        let fits = self.vec.len() <= 64
+            || self.vec[63..].all()
+            || self.vec[63..].not_any();
+        fits.then(|| self.as_i64())
+    }
+
+    /// Creates a Value of the given width from a signed integer, sign
+    /// extending to fill the width. Bits which do not fit are truncated, as
+    /// with [Value::from].
+    ///
+    /// # Example
+    /// ```
+    /// use interp::values::*;
+    /// let v = Value::from_signed(-2, 4);
+    /// assert_eq!(v, Value::from(0b1110, 4));
+    /// ```
+    pub fn from_signed(val: i64, width: u64) -> Self {
+        Value::from(val, width)
+    }
+
     /// Converts value into i128 type using 2C representation. Sign extends lower values.
     ///
     /// # Example
@@ -646,6 +829,177 @@ impl Value {
         let new_bv = BitVec::from_bitslice(&self.vec[lower_idx..=upper_idx]);
         Value { vec: new_bv }
     }
+
+    /// Returns a value containing the half-open bit range \[start,end\).
+    /// Unlike [Value::slice] this does not consume the original and errors
+    /// rather than panicking when the range does not fit in the value.
+    ///
+    /// # Example
+    /// ```
+    /// use interp::values::*;
+    /// let v = Value::from(0b1101, 4).bit_range(1, 3).unwrap();
+    /// assert_eq!(v, Value::from(0b10, 2));
+    /// ```
+    pub fn bit_range(
+        &self,
+        start: usize,
+        end: usize,
+    ) -> InterpreterResult<Self> {
+        if start > end || end > self.vec.len() {
+            return Err(InterpreterError::InvalidBitRange {
+                start,
+                end,
+                width: self.width(),
+            }
+            .into());
+        }
+
+        Ok(Value {
+            vec: BitVec::from_bitslice(&self.vec[start..end]),
+        })
+    }
+
+    /// Concatenates two values, placing `self` in the high bits and `other`
+    /// in the low bits. The result has width `self.width() + other.width()`.
+    /// Errors if the combined width cannot be represented.
+    ///
+    /// # Example
+    /// ```
+    /// use interp::values::*;
+    /// let v = Value::from(0b10, 2).concat(&Value::from(0b01, 3)).unwrap();
+    /// assert_eq!(v, Value::from(0b10001, 5));
+    /// ```
+    pub fn concat(&self, other: &Value) -> InterpreterResult<Self> {
+        let too_wide = || InterpreterError::ConcatTooWide {
+            high: self.width(),
+            low: other.width(),
+        };
+        let width = self
+            .vec
+            .len()
+            .checked_add(other.vec.len())
+            .filter(|w| *w <= BitSlice::<usize, Lsb0>::MAX_BITS)
+            .ok_or_else(too_wide)?;
+
+        let mut vec = BitString::with_capacity(width);
+        vec.extend_from_bitslice(&other.vec);
+        vec.extend_from_bitslice(&self.vec);
+        Ok(Value { vec })
+    }
+
+    /// Renders the value with a Verilog-style width prefix, e.g. `32'd1`.
+    /// The print code picks the radix: [PrintCode::Unsigned] gives `'d`,
+    /// [PrintCode::Signed] gives `'sd` with a leading minus sign for
+    /// negative values, [PrintCode::Binary] gives `'b`, and [PrintCode::Hex]
+    /// gives `'h`. Fixed point codes have no Verilog form and are rendered
+    /// as unsigned decimal.
+    ///
+    /// # Example
+    /// ```
+    /// use interp::{debugger::PrintCode, values::*};
+    /// let v = Value::from(0b1110, 4);
+    /// assert_eq!(v.to_string_with_width(PrintCode::Unsigned), "4'd14");
+    /// assert_eq!(v.to_string_with_width(PrintCode::Signed), "-4'sd2");
+    /// assert_eq!(v.to_string_with_width(PrintCode::Binary), "4'b1110");
+    /// assert_eq!(v.to_string_with_width(PrintCode::Hex), "4'he");
+    /// ```
+    pub fn to_string_with_width(&self, code: PrintCode) -> String {
+        let width = self.width();
+        match code {
+            PrintCode::Binary => {
+                let bits: String = self
+                    .vec
+                    .iter()
+                    .rev()
+                    .map(|bit| if *bit { '1' } else { '0' })
+                    .collect();
+                format!("{width}'b{bits}")
+            }
+            PrintCode::Hex => format!("{width}'h{:x}", self.as_unsigned()),
+            PrintCode::Signed => {
+                let val = self.as_signed();
+                if val < IBig::from(0) {
+                    format!("-{width}'sd{}", val.unsigned_abs())
+                } else {
+                    format!("{width}'sd{val}")
+                }
+            }
+            PrintCode::Unsigned
+            | PrintCode::UFixed(_)
+            | PrintCode::SFixed(_) => {
+                format!("{width}'d{}", self.as_unsigned())
+            }
+        }
+    }
+
+    /// Shifts the value left by `amt` bits, keeping the original width. Bits
+    /// shifted past the top are dropped and zeroes fill the bottom.
+    ///
+    /// # Example
+    /// ```
+    /// use interp::values::*;
+    /// let v = Value::from(0b1011, 4).shift_left(2);
+    /// assert_eq!(v, Value::from(0b1100, 4));
+    /// ```
+    pub fn shift_left(&self, amt: usize) -> Value {
+        if amt >= self.vec.len() {
+            return Value::zeroes(self.vec.len());
+        }
+        let mut vec = self.vec.clone();
+        // the vector is least significant bit first, so moving bits towards
+        // the back of it moves them towards the most significant end
+        vec.shift_right(amt);
+        Value { vec }
+    }
+
+    /// Logically shifts the value right by `amt` bits, keeping the original
+    /// width. Zeroes fill the top.
+    ///
+    /// # Example
+    /// ```
+    /// use interp::values::*;
+    /// let v = Value::from(0b1011, 4).shift_right(2);
+    /// assert_eq!(v, Value::from(0b0010, 4));
+    /// ```
+    pub fn shift_right(&self, amt: usize) -> Value {
+        if amt >= self.vec.len() {
+            return Value::zeroes(self.vec.len());
+        }
+        let mut vec = self.vec.clone();
+        vec.shift_left(amt);
+        Value { vec }
+    }
+
+    /// Bitwise and of two values of the same width
+    pub fn bitand(&self, other: &Value) -> InterpreterResult<Value> {
+        self.bitwise_op(other, |l, r| l & r)
+    }
+
+    /// Bitwise or of two values of the same width
+    pub fn bitor(&self, other: &Value) -> InterpreterResult<Value> {
+        self.bitwise_op(other, |l, r| l | r)
+    }
+
+    /// Bitwise exclusive or of two values of the same width
+    pub fn bitxor(&self, other: &Value) -> InterpreterResult<Value> {
+        self.bitwise_op(other, |l, r| l ^ r)
+    }
+
+    fn bitwise_op<F>(&self, other: &Value, op: F) -> InterpreterResult<Value>
+    where
+        F: FnOnce(BitString, &BitSlice<usize, Lsb0>) -> BitString,
+    {
+        if self.vec.len() != other.vec.len() {
+            return Err(InterpreterError::BitwiseWidthMismatch {
+                left: self.width(),
+                right: other.width(),
+            }
+            .into());
+        }
+        Ok(Value {
+            vec: op(self.vec.clone(), other.vec.as_bitslice()),
+        })
+    }
 }
 
 /* ============== Impls for Values to make them easier to use ============= */
@@ -681,7 +1035,7 @@ impl std::fmt::Display for Value {
 
 impl PartialEq for Value {
     fn eq(&self, other: &Self) -> bool {
-        self.vec.len() == other.vec.len() && *self.vec == *other.vec
+        self.bitwise_eq(other)
     }
 }
 
@@ -717,6 +1071,18 @@ impl Serialize for Value {
     }
 }
 
+/// The explicit form of a serialized [Value], giving its width alongside the
+/// number rather than rounding up to a whole number of bytes
+#[derive(serde::Deserialize)]
+#[serde(deny_unknown_fields)]
+struct SizedValue {
+    value: u64,
+    width: u64,
+}
+
+/// Values are read either from the base64 string written by [Serialize], or
+/// from an object of the form `{ "value": 5, "width": 4 }`. Objects whose
+/// value does not fit in the given width are rejected.
 impl<'de> Deserialize<'de> for Value {
     fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
     where
@@ -725,13 +1091,13 @@ impl<'de> Deserialize<'de> for Value {
         struct BitVecVisitor;
 
         impl<'de> Visitor<'de> for BitVecVisitor {
-            type Value = (UBig, usize);
+            type Value = Value;
 
             fn expecting(
                 &self,
                 formatter: &mut std::fmt::Formatter,
             ) -> std::fmt::Result {
-                formatter.write_str("Expected bitstring")
+                formatter.write_str("Expected bitstring or sized value")
             }
 
             fn visit_str<E>(self, value: &str) -> Result<Self::Value, E>
@@ -740,11 +1106,25 @@ impl<'de> Deserialize<'de> for Value {
             {
                 let s = base64::decode(value)
                     .expect("Couldn't convert from base64");
-                Ok((UBig::from_le_bytes(&s), s.len()))
+                Ok(Value::from(UBig::from_le_bytes(&s), s.len() * 8))
+            }
+
+            fn visit_map<A>(self, map: A) -> Result<Self::Value, A::Error>
+            where
+                A: de::MapAccess<'de>,
+            {
+                let SizedValue { value, width } = SizedValue::deserialize(
+                    de::value::MapAccessDeserializer::new(map),
+                )?;
+                match Value::from_checked(value, width) {
+                    (val, false) => Ok(val),
+                    (_, true) => Err(de::Error::custom(format!(
+                        "{value} does not fit in {width} bits"
+                    ))),
+                }
             }
         }
 
-        let (val, bytes) = deserializer.deserialize_str(BitVecVisitor)?;
-        Ok(Value::from(val, bytes * 8))
+        deserializer.deserialize_any(BitVecVisitor)
     }
 }
@@ -0,0 +1,323 @@
+#[cfg(test)]
+mod assignment_tests {
+    use crate::errors::InterpreterError;
+    use crate::interpreter::{get_dest_cells, get_src_cells};
+    use crate::interpreter_ir::{
+        Cell, ComponentBuilder, Guard, Port, PortParent, ValidationWarning,
+    };
+    use crate::tests::builder::helpers::assign;
+    use crate::utils::{arctex, ArcTex, WeakArcTex};
+    use calyx_ir::{self as orig_ir, Direction, Nothing, PortComp};
+    use std::sync::Weak;
+
+    #[test]
+    fn src_and_dest_cells() {
+        let mut builder = ComponentBuilder::new("main");
+        let sig_in = builder.add_input("in", 1);
+        let cell = |builder: &mut ComponentBuilder, name: &str| {
+            builder.add_cell(
+                name,
+                orig_ir::CellType::Constant { val: 0, width: 1 },
+                &[("in", 1, Direction::Input), ("out", 1, Direction::Output)],
+            )
+        };
+        let (a, b, c) = (
+            cell(&mut builder, "a"),
+            cell(&mut builder, "b"),
+            cell(&mut builder, "c"),
+        );
+        let assigns = vec![
+            assign(
+                &c.read().get("in"),
+                &a.read().get("out"),
+                Guard::Port(b.read().get("out")),
+            ),
+            assign(&a.read().get("in"), &sig_in, Guard::True),
+            assign(&b.read().get("in"), &a.read().get("out"), Guard::True),
+        ];
+
+        let names = |cells: Vec<ArcTex<Cell>>| {
+            cells.iter().map(|x| x.read().name()).collect::<Vec<_>>()
+        };
+        assert_eq!(names(get_src_cells(assigns.iter())), vec!["a", "b"]);
+        assert_eq!(
+            names(get_dest_cells(assigns.iter(), None)),
+            vec!["c", "a", "b"]
+        );
+    }
+
+    #[test]
+    fn assignment_display() {
+        let mut builder = ComponentBuilder::new("main");
+        let done = builder.add_output("done", 1);
+        let r = builder.add_cell(
+            "r",
+            orig_ir::CellType::Constant { val: 0, width: 1 },
+            &[
+                ("out", 1, Direction::Output),
+                ("done", 1, Direction::Output),
+            ],
+        );
+        let (out, r_done) = (r.read().get("out"), r.read().get("done"));
+
+        let guard: Guard<Nothing> = Guard::And(
+            Guard::Not(Guard::Port(out.clone()).into()).into(),
+            Guard::Or(
+                Guard::CompOp(PortComp::Eq, out.clone(), r_done.clone()).into(),
+                Guard::Port(r_done.clone()).into(),
+            )
+            .into(),
+        );
+        let guarded = assign(&done, &out, guard);
+        let unguarded = assign(&done, &r_done, Guard::True);
+
+        assert_eq!(
+            guarded.to_string(),
+            "main.done = !r.out & ((r.out == r.done) | r.done) ? r.out;"
+        );
+        assert_eq!(unguarded.to_string(), "main.done = r.done;");
+    }
+
+    #[test]
+    fn all_assignments() {
+        let mut builder = ComponentBuilder::new("main");
+        let (a, b, c) = (
+            builder.add_input("a", 1),
+            builder.add_output("b", 1),
+            builder.add_output("c", 1),
+        );
+        builder.add_continuous_assignment(assign(&b, &a, Guard::True));
+        builder.add_group("g").write().assignments.push(assign(
+            &c,
+            &a,
+            Guard::True,
+        ));
+        builder.add_comb_group("cg", vec![assign(&c, &b, Guard::True)]);
+        let comp = builder.build().unwrap();
+
+        let printed = comp
+            .all_assignments()
+            .map(|assign| assign.to_string())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            printed,
+            vec!["main.b = main.a;", "main.c = main.a;", "main.c = main.b;"]
+        );
+    }
+
+    #[test]
+    fn drivers_of() {
+        let mut builder = ComponentBuilder::new("main");
+        let (a, b, c) = (
+            builder.add_input("a", 1),
+            builder.add_input("b", 1),
+            builder.add_output("c", 1),
+        );
+        builder.add_continuous_assignment(assign(
+            &c,
+            &a,
+            Guard::Port(b.clone()),
+        ));
+        builder.add_group("g").write().assignments.push(assign(
+            &c,
+            &b,
+            Guard::Not(Guard::Port(b.clone()).into()),
+        ));
+        let comp = builder.build().unwrap();
+
+        let drivers = comp
+            .drivers_of(&c)
+            .iter()
+            .map(|assign| assign.to_string())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            drivers,
+            vec!["main.c = main.b ? main.a;", "main.c = !main.b ? main.b;"]
+        );
+        assert!(comp.drivers_of(&a).is_empty());
+    }
+
+    #[test]
+    fn single_driver() {
+        let ports = |builder: &mut ComponentBuilder| {
+            (
+                builder.add_input("a", 1),
+                builder.add_input("b", 1),
+                builder.add_output("c", 1),
+            )
+        };
+
+        let mut builder = ComponentBuilder::new("main");
+        let (a, b, c) = ports(&mut builder);
+        // groups never run together, so these do not conflict
+        for (name, src) in [("g1", &a), ("g2", &b)] {
+            let group = builder.add_group(name);
+            group.write().assignments.push(assign(&c, src, Guard::True));
+        }
+        builder.add_continuous_assignment(assign(
+            &c,
+            &a,
+            Guard::Port(b.clone()),
+        ));
+        let comp = builder.build().unwrap();
+        assert!(comp.check_single_driver().is_ok());
+
+        let mut builder = ComponentBuilder::new("main");
+        let (a, b, c) = ports(&mut builder);
+        let always = Guard::And(Guard::True.into(), Guard::True.into());
+        builder.add_continuous_assignment(assign(&c, &a, Guard::True));
+        let group = builder.add_group("g");
+        group.write().assignments.push(assign(&c, &b, always));
+        let comp = builder.build().unwrap();
+
+        match &*comp.check_single_driver().unwrap_err() {
+            InterpreterError::ConflictingAssignments {
+                port_id,
+                parent_id,
+                a1,
+                a2,
+            } => {
+                assert_eq!(*port_id, calyx_ir::Id::from("c"));
+                assert_eq!(*parent_id, calyx_ir::Id::from("main"));
+                assert_eq!(a1, "main.c = main.a;");
+                assert_eq!(a2, "main.c = 1 & 1 ? main.b;");
+            }
+            other => panic!("expected conflicting assignments, got {other}"),
+        }
+
+        // validation reports the same conflict as a warning
+        let warning = ValidationWarning::MultipleDrivers {
+            port: orig_ir::Canonical("main".into(), "c".into()),
+            scope: Some("g".into()),
+        };
+        assert_eq!(comp.validate().unwrap(), vec![warning]);
+    }
+
+    #[test]
+    fn ordered_assignments() {
+        let mut builder = ComponentBuilder::new("main");
+        let a = builder.add_input("a", 1);
+        let (x, y, z) = (
+            builder.add_output("x", 1),
+            builder.add_output("y", 1),
+            builder.add_output("z", 1),
+        );
+
+        let group = builder.add_group("g");
+        group.write().assignments.extend([
+            assign(&z, &a, Guard::Port(x.clone())),
+            assign(&x, &y, Guard::True),
+            assign(&y, &a, Guard::True),
+        ]);
+        let order = group
+            .read()
+            .ordered_assignments()
+            .unwrap()
+            .iter()
+            .map(|assign| assign.to_string())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            order,
+            vec![
+                "main.y = main.a;",
+                "main.x = main.y;",
+                "main.z = main.x ? main.a;"
+            ]
+        );
+
+        let cyclic = builder.add_group("cyclic");
+        cyclic.write().assignments.extend([
+            assign(&x, &y, Guard::True),
+            assign(&y, &a, Guard::Port(x.clone())),
+        ]);
+        let err = cyclic.read().ordered_assignments().unwrap_err();
+        assert!(matches!(*err, InterpreterError::CombinationalCycle(_)));
+    }
+
+    /// A combinational cell `name` with a one bit `in` and `out`
+    fn wire(builder: &mut ComponentBuilder, name: &str) -> ArcTex<Cell> {
+        builder.add_cell(
+            name,
+            orig_ir::CellType::Primitive {
+                name: "std_wire".into(),
+                param_binding: Box::new(vec![("WIDTH".into(), 1)]),
+                is_comb: true,
+                latency: None,
+            },
+            &[("in", 1, Direction::Input), ("out", 1, Direction::Output)],
+        )
+    }
+
+    fn names(cells: Vec<ArcTex<Cell>>) -> Vec<String> {
+        cells.iter().map(|x| x.read().name().to_string()).collect()
+    }
+
+    #[test]
+    fn topo_order_cells_per_group() {
+        let mut builder = ComponentBuilder::new("main");
+        let (a, b) = (wire(&mut builder, "a"), wire(&mut builder, "b"));
+        let orphan = arctex(Port {
+            name: "out".into(),
+            width: 1,
+            direction: Direction::Output,
+            parent: PortParent::Cell(WeakArcTex(Weak::new())),
+            attributes: Default::default(),
+            uid: 0,
+        });
+
+        // the groups connect the cells in opposite directions, which is not
+        // a loop since they never run together. The port whose parent was
+        // dropped is skipped.
+        let forward = builder.add_group("forward");
+        forward.write().assignments.push(assign(
+            &b.read().get("in"),
+            &a.read().get("out"),
+            Guard::Port(orphan),
+        ));
+        let backward = builder.add_group("backward");
+        backward.write().assignments.push(assign(
+            &a.read().get("in"),
+            &b.read().get("out"),
+            Guard::True,
+        ));
+        let comp = builder.build().unwrap();
+
+        assert_eq!(names(comp.topo_order_cells(None).unwrap()).len(), 2);
+        assert_eq!(
+            names(comp.topo_order_cells(Some(&forward)).unwrap()),
+            vec!["a", "b"]
+        );
+        assert_eq!(
+            names(comp.topo_order_cells(Some(&backward)).unwrap()),
+            vec!["b", "a"]
+        );
+    }
+
+    #[test]
+    fn topo_order_cells_cycle() {
+        let mut builder = ComponentBuilder::new("main");
+        let (a, b) = (wire(&mut builder, "a"), wire(&mut builder, "b"));
+        builder.add_continuous_assignment(assign(
+            &b.read().get("in"),
+            &a.read().get("out"),
+            Guard::True,
+        ));
+        let group = builder.add_group("g");
+        group.write().assignments.push(assign(
+            &a.read().get("in"),
+            &b.read().get("out"),
+            Guard::True,
+        ));
+        let comp = builder.build().unwrap();
+
+        assert_eq!(names(comp.topo_order_cells(None).unwrap()), vec!["a", "b"]);
+        let err = comp.topo_order_cells(Some(&group)).unwrap_err();
+        let InterpreterError::CombinationalCycle(cycle) = &*err else {
+            panic!("expected a combinational cycle, got {err:?}");
+        };
+        assert_eq!(cycle.len(), 3);
+        assert_eq!(cycle[0], cycle[2]);
+        assert!(cycle.contains(&"a".to_string()));
+        assert!(cycle.contains(&"b".to_string()));
+    }
+}
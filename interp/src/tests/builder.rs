@@ -0,0 +1,392 @@
+/// Shorthands for putting components together by hand, shared by the tests
+/// of the interpreter IR
+#[cfg(test)]
+pub(super) mod helpers {
+    use crate::interpreter_ir::{
+        Assignment, Control, Enable, Group, Guard, Port,
+    };
+    use crate::utils::ArcTex;
+    use calyx_ir::Nothing;
+    use std::sync::Arc;
+
+    pub fn enable(group: ArcTex<Group>) -> Control {
+        Control::Enable(
+            Enable {
+                group,
+                attributes: Default::default(),
+            }
+            .into(),
+        )
+    }
+
+    /// The assignment `dst = guard ? src`
+    pub fn assign(
+        dst: &ArcTex<Port>,
+        src: &ArcTex<Port>,
+        guard: Guard<Nothing>,
+    ) -> Assignment<Nothing> {
+        Assignment {
+            dst: dst.clone(),
+            src: src.clone(),
+            guard: Arc::new(guard),
+            attributes: Default::default(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod builder_tests {
+    use super::helpers::enable;
+    use crate::errors::InterpreterError;
+    use crate::interpreter_ir::{
+        Cell, ComponentBuilder, Control, Port, StaticControl, StaticSeq,
+    };
+    use crate::utils::ArcTex;
+    use calyx_ir::{self as orig_ir, Direction};
+    use std::num::NonZeroU64;
+    use std::sync::Arc;
+
+    #[test]
+    fn builds_component() {
+        let mut builder = ComponentBuilder::new("main");
+        builder.add_input("in", 8);
+        builder.add_output("out", 8);
+        let cell = builder.add_cell(
+            "r",
+            orig_ir::CellType::Constant { val: 0, width: 8 },
+            &[("out", 8, Direction::Output)],
+        );
+        let group = builder.add_group("g");
+        builder.set_control(enable(group.clone()));
+
+        let comp = builder.build().unwrap();
+        assert_eq!(comp.signature.read().ports().len(), 2);
+        assert!(comp.find_cell("r").is_some());
+        assert_eq!(cell.read().get("out").read().get_parent_name(), "r");
+        let found = comp.find_group("g").unwrap();
+        assert!(found.read().find("go").is_some());
+        assert!(found.read().find("done").is_some());
+        assert_eq!(comp.control.count_enables(), 1);
+    }
+
+    #[test]
+    fn rejects_unknown_enable() {
+        let mut builder = ComponentBuilder::new("main");
+        builder.add_group("g");
+        let stray = ComponentBuilder::new("other").add_group("h");
+        builder.set_control(enable(stray));
+
+        match &*builder.build().unwrap_err() {
+            InterpreterError::UndefinedGroupEnable(name) => {
+                assert_eq!(*name, calyx_ir::Id::from("h"))
+            }
+            other => panic!("expected an undefined group error, got {other}"),
+        }
+    }
+
+    #[test]
+    fn incomplete_group_detected() {
+        let mut builder = ComponentBuilder::new("main");
+        builder.add_group("complete");
+        let partial = builder.add_group("partial");
+        let comp = builder.build().unwrap();
+        partial.write().holes.clear();
+
+        match &*comp.assert_fully_translated().unwrap_err() {
+            InterpreterError::PartiallyTranslatedGroup(name) => {
+                assert_eq!(*name, calyx_ir::Id::from("partial"))
+            }
+            other => panic!("expected a partial group error, got {other}"),
+        }
+    }
+
+    #[test]
+    fn id_list_indexing() {
+        let mut builder = ComponentBuilder::new("main");
+        for name in ["a", "b", "c"] {
+            builder.add_group(name);
+        }
+        let comp = builder.build().unwrap();
+
+        assert_eq!(comp.groups.len(), 3);
+        assert!(!comp.groups.is_empty());
+        assert!(comp.comb_groups.is_empty());
+        assert_eq!(comp.groups.get_index(1).unwrap().read().name(), "b");
+        assert!(comp.groups.get_index(3).is_none());
+        assert_eq!(comp.groups.find("c").unwrap().read().name(), "c");
+    }
+
+    #[test]
+    fn write_all_visits_in_order() {
+        let mut builder = ComponentBuilder::new("main");
+        for name in ["a", "b"] {
+            builder.add_group(name);
+        }
+        let comp = builder.build().unwrap();
+
+        let mut seen = vec![];
+        comp.groups.write_all(|group| {
+            seen.push(group.name());
+            group.holes.clear();
+        });
+
+        assert_eq!(seen, vec!["a", "b"]);
+        assert!(comp.groups.iter().all(|g| g.read().holes.is_empty()));
+    }
+
+    #[test]
+    fn signature_ports() {
+        let mut builder = ComponentBuilder::new("main");
+        builder.add_input("a", 1);
+        builder.add_output("out", 1);
+        builder.add_input("b", 1);
+        let comp = builder.build().unwrap();
+
+        let names = |ports: Vec<ArcTex<Port>>| {
+            ports.iter().map(|p| p.read().name).collect::<Vec<_>>()
+        };
+        assert_eq!(names(comp.input_ports().collect()), vec!["a", "b"]);
+        assert_eq!(names(comp.output_ports().collect()), vec!["out"]);
+    }
+
+    #[test]
+    fn sort_ports() {
+        let mut builder = ComponentBuilder::new("main");
+        builder.add_output("done", 1);
+        builder.add_input("go", 1);
+        let cell = builder.add_cell(
+            "r",
+            orig_ir::CellType::Constant { val: 0, width: 1 },
+            &[
+                ("out", 1, Direction::Output),
+                ("in", 1, Direction::Input),
+                ("clk", 1, Direction::Input),
+            ],
+        );
+        let comp = builder.build().unwrap();
+        comp.sort_ports();
+
+        let names = |cell: &ArcTex<Cell>| {
+            let cell = cell.read();
+            cell.ports()
+                .iter()
+                .map(|p| p.read().name)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(names(&cell), vec!["clk", "in", "out"]);
+        assert_eq!(names(&comp.signature), vec!["done", "go"]);
+    }
+
+    #[test]
+    fn cell_ports_by_direction() {
+        let mut builder = ComponentBuilder::new("main");
+        let cell = builder.add_cell(
+            "r",
+            orig_ir::CellType::Constant { val: 0, width: 1 },
+            &[
+                ("in", 1, Direction::Input),
+                ("out", 1, Direction::Output),
+                ("write_en", 1, Direction::Input),
+                ("done", 1, Direction::Output),
+            ],
+        );
+
+        let cell = cell.read();
+        let names = |ports: Vec<&ArcTex<Port>>| {
+            ports.iter().map(|p| p.read().name).collect::<Vec<_>>()
+        };
+        assert_eq!(names(cell.input_ports().collect()), vec!["in", "write_en"]);
+        assert_eq!(names(cell.output_ports().collect()), vec!["out", "done"]);
+    }
+
+    #[test]
+    fn holes_and_cell_ports() {
+        let mut builder = ComponentBuilder::new("main");
+        let input = builder.add_input("in", 1);
+        let group = builder.add_group("g");
+        let go = group.read().get("go");
+
+        assert!(go.read().is_hole());
+        assert!(!go.read().is_cell_port());
+        assert!(input.read().is_cell_port());
+        assert!(!input.read().is_hole());
+    }
+
+    #[test]
+    fn primitives() {
+        let mut builder = ComponentBuilder::new("main");
+        builder.add_cell(
+            "add",
+            orig_ir::CellType::Primitive {
+                name: "std_add".into(),
+                param_binding: Box::new(vec![("WIDTH".into(), 32)]),
+                is_comb: true,
+                latency: None,
+            },
+            &[],
+        );
+        builder.add_cell(
+            "c",
+            orig_ir::CellType::Constant { val: 1, width: 1 },
+            &[],
+        );
+        builder.add_cell(
+            "sub",
+            orig_ir::CellType::Component { name: "sub".into() },
+            &[],
+        );
+        let comp = builder.build().unwrap();
+
+        let primitives = comp.primitives().collect::<Vec<_>>();
+        assert_eq!(
+            primitives,
+            vec![("add".into(), "std_add".into(), vec![("WIDTH".into(), 32)])]
+        );
+    }
+
+    #[test]
+    fn pipeline_latency() {
+        let prim = |name: &str, latency| orig_ir::CellType::Primitive {
+            name: name.into(),
+            param_binding: Box::new(vec![("WIDTH".into(), 32)]),
+            is_comb: false,
+            latency: NonZeroU64::new(latency),
+        };
+        let mut builder = ComponentBuilder::new("main");
+        let table = builder.add_cell("mult", prim("std_mult_pipe", 0), &[]);
+        let div = builder.add_cell("div", prim("std_div_pipe", 0), &[]);
+        let declared =
+            builder.add_cell("declared", prim("std_mult_pipe", 4), &[]);
+        let attr = builder.add_cell("attr", prim("std_mult_pipe", 4), &[]);
+        attr.write().attributes.insert(orig_ir::NumAttr::Static, 2);
+
+        // the attribute wins over the declared latency, which wins over the
+        // table
+        assert_eq!(attr.read().pipeline_latency(), Some(2));
+        assert_eq!(declared.read().pipeline_latency(), Some(4));
+        assert_eq!(table.read().pipeline_latency(), Some(3));
+        assert_eq!(div.read().pipeline_latency(), None);
+    }
+
+    #[test]
+    fn static_latency() {
+        let empty = ComponentBuilder::new("main").build().unwrap();
+        assert_eq!(empty.total_static_latency(), Some(0));
+
+        let mut builder = ComponentBuilder::new("main");
+        builder.set_control(Control::Static(StaticControl::Seq(
+            StaticSeq {
+                stmts: vec![],
+                attributes: Default::default(),
+                latency: 5,
+            }
+            .into(),
+        )));
+        let fixed = builder.build().unwrap();
+        assert_eq!(fixed.total_static_latency(), Some(5));
+
+        let mut builder = ComponentBuilder::new("main");
+        let group = builder.add_group("g");
+        builder.set_control(enable(group));
+        let dynamic = builder.build().unwrap();
+        assert_eq!(dynamic.total_static_latency(), None);
+    }
+}
+
+#[cfg(test)]
+mod clone_tests {
+    use super::helpers::{assign, enable};
+    use crate::interpreter_ir::{ComponentBuilder, Control, Guard, PortParent};
+    use calyx_ir::{self as orig_ir, Direction};
+    use std::sync::Arc;
+
+    #[test]
+    fn deep_clone_is_independent() {
+        let mut builder = ComponentBuilder::new("main");
+        let sig_in = builder.add_input("in", 1);
+        let r = builder.add_cell(
+            "r",
+            orig_ir::CellType::Constant { val: 0, width: 1 },
+            &[("in", 1, Direction::Input), ("out", 1, Direction::Output)],
+        );
+        let group = builder.add_group("g");
+        group.write().assignments.push(assign(
+            &r.read().get("in"),
+            &sig_in,
+            Guard::Port(r.read().get("out")),
+        ));
+        builder.set_control(enable(group));
+        let comp = builder.build().unwrap();
+
+        let copy = comp.deep_clone();
+        let (orig_r, copy_r) =
+            (comp.find_cell("r").unwrap(), copy.find_cell("r").unwrap());
+        assert!(!Arc::ptr_eq(&orig_r, &copy_r));
+
+        let copy_out = copy_r.read().get("out");
+        assert!(!Arc::ptr_eq(&orig_r.read().get("out"), &copy_out));
+        match &copy_out.read().parent {
+            PortParent::Cell(parent) => {
+                assert!(Arc::ptr_eq(&parent.upgrade(), &copy_r))
+            }
+            _ => panic!("expected a cell parent"),
+        }
+
+        let copy_group = copy.find_group("g").unwrap();
+        let Control::Enable(enabled) = &copy.control else {
+            panic!("expected an enable")
+        };
+        assert!(Arc::ptr_eq(&enabled.group, &copy_group));
+
+        let assign = copy_group.read().assignments[0].clone();
+        assert!(Arc::ptr_eq(&assign.dst, &copy_r.read().get("in")));
+        assert!(Arc::ptr_eq(&assign.src, &copy.signature.read().get("in")));
+        let Guard::Port(guard_port) = &*assign.guard else {
+            panic!("expected a port guard")
+        };
+        assert!(Arc::ptr_eq(guard_port, &copy_out));
+
+        copy_out.write().width = 2;
+        assert_eq!(orig_r.read().get("out").read().width, 1);
+    }
+
+    #[test]
+    fn clone_shallow_shares_state() {
+        let mut builder = ComponentBuilder::new("main");
+        builder.add_group("g");
+        let comp = builder.build().unwrap();
+
+        let shallow = comp.clone_shallow();
+        let (orig, shared) = (
+            comp.find_group("g").unwrap(),
+            shallow.find_group("g").unwrap(),
+        );
+        assert!(Arc::ptr_eq(&orig, &shared));
+
+        shared.write().holes.clear();
+        assert!(orig.read().holes.is_empty());
+    }
+
+    #[test]
+    fn structural_hash() {
+        let build = |negate: bool| {
+            let mut builder = ComponentBuilder::new("main");
+            let (a, b) =
+                (builder.add_input("a", 1), builder.add_output("b", 1));
+            let group = builder.add_group("g");
+            let guard = if negate {
+                Guard::Not(Guard::Port(a.clone()).into())
+            } else {
+                Guard::Port(a.clone())
+            };
+            group.write().assignments.push(assign(&b, &a, guard));
+            builder.set_control(enable(group));
+            builder.build().unwrap()
+        };
+
+        let comp = build(false);
+        assert_eq!(comp.structural_hash(), build(false).structural_hash());
+        assert_eq!(comp.structural_hash(), comp.deep_clone().structural_hash());
+        assert_ne!(comp.structural_hash(), build(true).structural_hash());
+    }
+}
@@ -0,0 +1,245 @@
+#[cfg(test)]
+mod control_tests {
+    use crate::errors::InterpreterError;
+    use crate::interpreter::{check_supported, control_is_empty};
+    use crate::interpreter_ir::{
+        Control, Empty, Enable, Par, Repeat, Seq, StaticControl, StaticSeq,
+        TranslationMap,
+    };
+    use calyx_ir as orig_ir;
+
+    fn enable(translator: &mut TranslationMap, name: &str) -> Control {
+        let group = orig_ir::rrc(orig_ir::Group::new(name.into()));
+        Control::Enable(
+            Enable {
+                group: translator.get_group(&group),
+                attributes: Default::default(),
+            }
+            .into(),
+        )
+    }
+
+    fn seq(stmts: Vec<Control>) -> Control {
+        Control::Seq(
+            Seq {
+                stmts,
+                attributes: Default::default(),
+            }
+            .into(),
+        )
+    }
+
+    fn par(stmts: Vec<Control>) -> Control {
+        Control::Par(
+            Par {
+                stmts,
+                attributes: Default::default(),
+            }
+            .into(),
+        )
+    }
+
+    fn repeat(num_repeats: u64, body: Control) -> Control {
+        Control::Repeat(
+            Repeat {
+                body,
+                num_repeats,
+                attributes: Default::default(),
+            }
+            .into(),
+        )
+    }
+
+    #[test]
+    fn nested_par_seq() {
+        let mut tm = TranslationMap::new();
+        let empty = Control::Empty(
+            Empty {
+                attributes: Default::default(),
+            }
+            .into(),
+        );
+
+        let program = seq(vec![
+            enable(&mut tm, "a"),
+            par(vec![
+                enable(&mut tm, "b"),
+                seq(vec![enable(&mut tm, "c"), empty.clone()]),
+                par(vec![]),
+            ]),
+            seq(vec![par(vec![enable(&mut tm, "d")]), enable(&mut tm, "a")]),
+        ]);
+
+        assert_eq!(program.count_enables(), 5);
+        assert_eq!(empty.count_enables(), 0);
+    }
+
+    #[test]
+    fn max_par_width_nested() {
+        let mut tm = TranslationMap::new();
+
+        assert_eq!(enable(&mut tm, "a").max_par_width(), 1);
+        assert_eq!(par(vec![]).max_par_width(), 1);
+
+        let flat = seq(vec![enable(&mut tm, "a"), enable(&mut tm, "b")]);
+        assert_eq!(flat.max_par_width(), 1);
+
+        // par { par { a; b; c }; seq { d; par { e; f } } }
+        let program = par(vec![
+            par(vec![
+                enable(&mut tm, "a"),
+                enable(&mut tm, "b"),
+                enable(&mut tm, "c"),
+            ]),
+            seq(vec![
+                enable(&mut tm, "d"),
+                par(vec![enable(&mut tm, "e"), enable(&mut tm, "f")]),
+            ]),
+        ]);
+        assert_eq!(program.max_par_width(), 5);
+
+        // a seq only takes its widest statement
+        let program = seq(vec![
+            par(vec![enable(&mut tm, "a"), enable(&mut tm, "b")]),
+            par(vec![
+                enable(&mut tm, "c"),
+                enable(&mut tm, "d"),
+                enable(&mut tm, "e"),
+            ]),
+        ]);
+        assert_eq!(program.max_par_width(), 3);
+    }
+
+    #[test]
+    fn repeat_is_empty() {
+        let mut tm = TranslationMap::new();
+
+        // zero iterations never run the body
+        assert!(control_is_empty(&repeat(0, enable(&mut tm, "a"))));
+        // any number of iterations of nothing is nothing
+        assert!(control_is_empty(&repeat(3, seq(vec![]))));
+        assert!(control_is_empty(&repeat(3, par(vec![seq(vec![])]))));
+
+        assert!(!control_is_empty(&repeat(1, enable(&mut tm, "a"))));
+        assert!(!control_is_empty(&seq(vec![
+            repeat(0, enable(&mut tm, "a")),
+            enable(&mut tm, "b"),
+        ])));
+    }
+
+    #[test]
+    fn pretty_print_nesting() {
+        let mut tm = TranslationMap::new();
+        let program = seq(vec![
+            enable(&mut tm, "a"),
+            par(vec![enable(&mut tm, "b"), enable(&mut tm, "c")]),
+        ]);
+
+        let expected =
+            "  seq {\n    a;\n    par {\n      b;\n      c;\n    }\n  }\n";
+        assert_eq!(program.pretty_print(2), expected);
+
+        let outline = "seq\n  enable a\n  par\n    enable b\n    enable c\n";
+        assert_eq!(program.pretty_tree(), outline);
+    }
+
+    #[test]
+    fn static_control_unsupported() {
+        let mut tm = TranslationMap::new();
+        let supported = seq(vec![enable(&mut tm, "a")]);
+        assert!(check_supported(&supported).is_ok());
+
+        let fixed = Control::Static(StaticControl::Seq(
+            StaticSeq {
+                stmts: vec![],
+                attributes: Default::default(),
+                latency: 1,
+            }
+            .into(),
+        ));
+        let program = seq(vec![enable(&mut tm, "b"), par(vec![fixed])]);
+        match &*check_supported(&program).unwrap_err() {
+            InterpreterError::UnsupportedConstruct { construct } => {
+                assert_eq!(*construct, "static control")
+            }
+            other => panic!("expected UnsupportedConstruct, got {other}"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod control_structure_tests {
+    use crate::errors::InterpreterError;
+    use crate::interpreter_ir::{ComponentBuilder, Control, Invoke, Seq};
+    use crate::tests::builder::helpers::enable;
+    use calyx_ir::{self as orig_ir, Direction};
+
+    #[test]
+    fn invoke_validate() {
+        let mut builder = ComponentBuilder::new("main");
+        let a = builder.add_input("a", 32);
+        let out = builder.add_output("out", 32);
+        let sub = builder.add_cell(
+            "sub",
+            orig_ir::CellType::Component { name: "sub".into() },
+            &[("in", 32, Direction::Input), ("out", 32, Direction::Output)],
+        );
+        let invoke = |inputs: &[&str], outputs: &[&str]| Invoke {
+            comp: sub.clone(),
+            inputs: inputs.iter().map(|&p| (p.into(), a.clone())).collect(),
+            outputs: outputs.iter().map(|&p| (p.into(), out.clone())).collect(),
+            attributes: Default::default(),
+            comb_group: None,
+            ref_cells: vec![],
+        };
+
+        assert!(invoke(&["in"], &["out"]).validate().is_ok());
+
+        // `out` exists but is not an input
+        match &*invoke(&["out"], &[]).validate().unwrap_err() {
+            InterpreterError::InvalidInvokePort {
+                cell,
+                port,
+                expected,
+            } => {
+                assert_eq!(cell, "sub");
+                assert_eq!(port, "out");
+                assert_eq!(*expected, "input");
+            }
+            other => panic!("expected an invalid invoke port, got {other}"),
+        }
+        assert!(invoke(&["in"], &["missing"]).validate().is_err());
+    }
+
+    #[test]
+    fn control_structural_eq() {
+        use orig_ir::NumAttr::{ReadTogether, WriteTogether};
+
+        let build = |group_name: &str, attrs: &[(orig_ir::NumAttr, u64)]| {
+            let mut builder = ComponentBuilder::new("main");
+            let group = builder.add_group(group_name);
+            let mut attributes = orig_ir::Attributes::default();
+            for (attr, val) in attrs {
+                attributes.insert(*attr, *val);
+            }
+            builder.set_control(Control::Seq(
+                Seq {
+                    stmts: vec![enable(group)],
+                    attributes,
+                }
+                .into(),
+            ));
+            builder.build().unwrap()
+        };
+
+        let a = build("g", &[(ReadTogether, 1), (WriteTogether, 2)]);
+        // separately built, with the attributes added in the other order
+        let b = build("g", &[(WriteTogether, 2), (ReadTogether, 1)]);
+        assert_eq!(a.control, b.control);
+
+        let renamed = build("h", &[(ReadTogether, 1), (WriteTogether, 2)]);
+        assert_ne!(a.control, renamed.control);
+        let fewer_attrs = build("g", &[(ReadTogether, 1)]);
+        assert_ne!(a.control, fewer_attrs.control);
+    }
+}
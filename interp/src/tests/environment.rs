@@ -0,0 +1,81 @@
+#[cfg(test)]
+mod environment_tests {
+    use crate::configuration::Config;
+    use crate::environment::InterpreterState;
+    use crate::errors::{InterpreterError, InterpreterResult};
+    use crate::interpreter_ir::{Cell, Component, ComponentBuilder};
+    use crate::utils::{ArcTex, MemoryMap};
+    use crate::values::Value;
+    use calyx_ir::{self as orig_ir, Direction, Id};
+    use std::collections::HashMap;
+    use std::sync::Arc;
+
+    /// A component holding only an 8 bit register `r`
+    fn reg_component() -> (Arc<Component>, ArcTex<Cell>) {
+        let mut builder = ComponentBuilder::new("main");
+        let reg = builder.add_cell(
+            "r",
+            orig_ir::CellType::Primitive {
+                name: "std_reg".into(),
+                param_binding: Box::new(vec![("WIDTH".into(), 8)]),
+                is_comb: false,
+                latency: None,
+            },
+            &[
+                ("in", 8, Direction::Input),
+                ("write_en", 1, Direction::Input),
+                ("reset", 1, Direction::Input),
+                ("out", 8, Direction::Output),
+                ("done", 1, Direction::Output),
+            ],
+        );
+        (Arc::new(builder.build().unwrap()), reg)
+    }
+
+    fn init(
+        comp: &Arc<Component>,
+        seed: Value,
+    ) -> InterpreterResult<InterpreterState> {
+        let ctx = Arc::new(vec![Arc::clone(comp)]);
+        let mut mems: Option<MemoryMap> =
+            Some(HashMap::from([(Id::from("r"), vec![seed])]).into());
+        InterpreterState::init_top_level(
+            &ctx,
+            comp,
+            &mut mems,
+            &Config::default(),
+        )
+    }
+
+    #[test]
+    fn seeded_register_reads_back() {
+        let (comp, reg) = reg_component();
+        let env = init(&comp, Value::from(42, 8)).unwrap();
+
+        assert_eq!(env.clk, 0);
+        let out = reg.read().get("out");
+        assert_eq!(env.get_from_port(&*out.read()), &Value::from(42, 8));
+        let done = reg.read().get("done");
+        assert_eq!(env.get_from_port(&*done.read()), &Value::bit_low());
+
+        // a narrower seed is zero extended
+        let env = init(&comp, Value::from(3, 2)).unwrap();
+        assert_eq!(env.get_from_port(&*out.read()), &Value::from(3, 8));
+    }
+
+    #[test]
+    fn oversized_register_seed() {
+        let (comp, _) = reg_component();
+        // wider than the register but still representable
+        assert!(init(&comp, Value::from(255, 16)).is_ok());
+
+        match &*init(&comp, Value::from(256, 16)).unwrap_err() {
+            InterpreterError::MemoryInitOverflow { cell, index, width } => {
+                assert_eq!(cell, "r");
+                assert_eq!(*index, 0);
+                assert_eq!(*width, 8);
+            }
+            other => panic!("expected a memory overflow, got {other}"),
+        }
+    }
+}
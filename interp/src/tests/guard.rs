@@ -0,0 +1,211 @@
+#[cfg(test)]
+mod guard_eval_tests {
+    use crate::interpreter_ir::{
+        apply_port_comp, Cell, Guard, Port, PortParent,
+    };
+    use crate::utils::{arctex, ArcTex, WeakArcTex};
+    use crate::values::Value;
+    use calyx_ir::{CellType, Direction, Nothing, PortComp};
+    use std::sync::Weak;
+
+    fn port(name: &str) -> ArcTex<Port> {
+        arctex(Port {
+            name: name.into(),
+            width: 4,
+            direction: Direction::Output,
+            parent: PortParent::Cell(WeakArcTex(Weak::new())),
+            attributes: Default::default(),
+            uid: 0,
+        })
+    }
+
+    /// Evaluates `left op right` with `left` reading 3 and `right` reading
+    /// `right_val`
+    fn compare(op: PortComp, right_val: u64) -> bool {
+        let (l, r) = (port("left"), port("right"));
+        let guard: Guard<Nothing> = Guard::CompOp(op, l, r);
+        guard.eval(|p| {
+            if p.read().name == "left" {
+                Value::from(3, 4)
+            } else {
+                Value::from(right_val, 4)
+            }
+        })
+    }
+
+    #[test]
+    fn comparisons() {
+        assert!(compare(PortComp::Eq, 3));
+        assert!(!compare(PortComp::Eq, 4));
+        assert!(compare(PortComp::Neq, 4));
+        assert!(!compare(PortComp::Neq, 3));
+        assert!(compare(PortComp::Gt, 2));
+        assert!(!compare(PortComp::Gt, 3));
+        assert!(compare(PortComp::Lt, 4));
+        assert!(!compare(PortComp::Lt, 3));
+        assert!(compare(PortComp::Geq, 3));
+        assert!(!compare(PortComp::Geq, 4));
+        assert!(compare(PortComp::Leq, 3));
+        assert!(!compare(PortComp::Leq, 2));
+    }
+
+    #[test]
+    fn logical_ops() {
+        let high: Guard<Nothing> = Guard::Port(port("high"));
+        let low: Guard<Nothing> = Guard::Port(port("low"));
+        let read =
+            |p: &ArcTex<Port>| Value::from((p.read().name == "high") as u64, 1);
+
+        assert!(high.eval(read));
+        assert!(!low.eval(read));
+        assert!(Guard::<Nothing>::True.eval(read));
+        assert!(Guard::Not(low.clone().into()).eval(read));
+        assert!(Guard::Or(low.clone().into(), high.clone().into()).eval(read));
+        assert!(!Guard::And(low.into(), high.into()).eval(read));
+    }
+
+    #[test]
+    fn referenced_ports_dedup_in_order() {
+        let (a, b, c) = (port("a"), port("b"), port("c"));
+        let guard: Guard<Nothing> = Guard::And(
+            Guard::CompOp(PortComp::Lt, b.clone(), a.clone()).into(),
+            Guard::Or(
+                Guard::Port(c.clone()).into(),
+                Guard::Not(Guard::Port(b.clone()).into()).into(),
+            )
+            .into(),
+        );
+
+        let names = guard
+            .referenced_ports()
+            .iter()
+            .map(|p| p.read().name)
+            .collect::<Vec<_>>();
+        assert_eq!(names, vec!["b", "a", "c"]);
+        assert!(Guard::<Nothing>::True.referenced_ports().is_empty());
+    }
+
+    #[test]
+    fn simplify_constants() {
+        let p = Guard::<Nothing>::Port(port("p"));
+        let t = || Box::new(Guard::<Nothing>::True);
+
+        let g = Guard::And(t(), p.clone().into()).simplified();
+        assert!(matches!(g, Guard::Port(_)));
+        let g = Guard::Or(p.clone().into(), t()).simplified();
+        assert!(matches!(g, Guard::True));
+        let g = Guard::Not(Guard::Not(p.clone().into()).into()).simplified();
+        assert!(matches!(g, Guard::Port(_)));
+        let g =
+            Guard::And(p.clone().into(), Guard::Not(t()).into()).simplified();
+        assert!(matches!(g, Guard::False));
+        let g = Guard::Or(Guard::Not(t()).into(), p.into()).simplified();
+        assert!(matches!(g, Guard::Port(_)));
+    }
+
+    #[test]
+    fn simplify_is_idempotent() {
+        let (a, b) = (port("a"), port("b"));
+        let guards: Vec<Guard<Nothing>> = vec![
+            Guard::Not(
+                Guard::Not(
+                    Guard::And(
+                        Guard::True.into(),
+                        Guard::Not(
+                            Guard::Not(Guard::Port(a.clone()).into()).into(),
+                        )
+                        .into(),
+                    )
+                    .into(),
+                )
+                .into(),
+            ),
+            Guard::Or(
+                Guard::CompOp(PortComp::Eq, a.clone(), b.clone()).into(),
+                Guard::Not(
+                    Guard::Or(Guard::True.into(), Guard::Port(b).into()).into(),
+                )
+                .into(),
+            ),
+            Guard::Not(Guard::Port(a).into()),
+        ];
+
+        let read =
+            |p: &ArcTex<Port>| Value::from((p.read().name == "a") as u64, 1);
+        for guard in guards {
+            let once = guard.simplified();
+            let twice = once.simplified();
+            assert_eq!(format!("{once:?}"), format!("{twice:?}"));
+            assert_eq!(guard.eval(read), once.eval(read));
+        }
+    }
+
+    #[test]
+    fn display_parenthesizes() {
+        let cell = arctex(Cell::new("c".into(), CellType::ThisComponent));
+        let port = |name: &str| -> Box<Guard<Nothing>> {
+            let port = arctex(Port {
+                name: name.into(),
+                width: 1,
+                direction: Direction::Output,
+                parent: WeakArcTex::from(&cell).into(),
+                attributes: Default::default(),
+                uid: 0,
+            });
+            cell.write().ports.push(port.clone());
+            Guard::Port(port).into()
+        };
+        let (a, b, c) = (port("a"), port("b"), port("c"));
+
+        let nested = Guard::And(a.clone(), Guard::Or(b.clone(), c).into());
+        assert_eq!(nested.to_string(), "c.a & (c.b | c.c)");
+
+        let flat = Guard::And(Guard::And(a.clone(), b.clone()).into(), a);
+        assert_eq!(flat.to_string(), "c.a & c.b & c.a");
+
+        let negated = Guard::Not(Guard::Or(b, Guard::True.into()).into());
+        assert_eq!(negated.to_string(), "!(c.b | 1)");
+    }
+
+    #[test]
+    fn port_comp_boundaries() {
+        let v = |n: u64| Value::from(n, 4);
+        // (lhs, rhs, [eq, neq, gt, lt, geq, leq])
+        let cases = [
+            (0, 0, [true, false, false, false, true, true]),
+            (15, 15, [true, false, false, false, true, true]),
+            (0, 15, [false, true, false, true, false, true]),
+            (15, 0, [false, true, true, false, true, false]),
+            (8, 7, [false, true, true, false, true, false]),
+        ];
+        let ops = [
+            PortComp::Eq,
+            PortComp::Neq,
+            PortComp::Gt,
+            PortComp::Lt,
+            PortComp::Geq,
+            PortComp::Leq,
+        ];
+        for (lhs, rhs, expected) in cases {
+            for (op, want) in ops.iter().zip(expected) {
+                assert_eq!(
+                    apply_port_comp(op, &v(lhs), &v(rhs)),
+                    want,
+                    "{lhs} {op:?} {rhs}"
+                );
+            }
+        }
+
+        // differing widths compare numerically
+        assert!(apply_port_comp(
+            &PortComp::Eq,
+            &Value::from(3, 2),
+            &Value::from(3, 8)
+        ));
+        assert!(apply_port_comp(
+            &PortComp::Lt,
+            &Value::from(3, 2),
+            &Value::from(4, 8)
+        ));
+    }
+}
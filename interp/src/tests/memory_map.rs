@@ -0,0 +1,430 @@
+#[cfg(test)]
+mod diff_tests {
+    use crate::utils::MemoryMap;
+    use crate::values::Value;
+    use calyx_ir::Id;
+    use std::collections::HashMap;
+
+    fn mem_map(entries: Vec<(&str, Vec<Value>)>) -> MemoryMap {
+        entries
+            .into_iter()
+            .map(|(name, vals)| (Id::from(name), vals))
+            .collect::<HashMap<_, _>>()
+            .into()
+    }
+
+    #[test]
+    fn identical_maps_have_no_diff() {
+        let a =
+            mem_map(vec![("mem", vec![Value::from(1, 8), Value::from(2, 8)])]);
+        let b =
+            mem_map(vec![("mem", vec![Value::from(1, 8), Value::from(2, 8)])]);
+        assert!(a.diff(&b).is_empty());
+    }
+
+    #[test]
+    fn mismatched_value() {
+        let expected =
+            mem_map(vec![("mem", vec![Value::from(1, 8), Value::from(2, 8)])]);
+        let actual =
+            mem_map(vec![("mem", vec![Value::from(1, 8), Value::from(3, 8)])]);
+        let diff = expected.diff(&actual);
+        assert_eq!(diff.len(), 1);
+        assert_eq!(diff[0].cell, Id::from("mem"));
+        assert_eq!(diff[0].index, 1);
+        assert_eq!(diff[0].expected, Some(Value::from(2, 8)));
+        assert_eq!(diff[0].actual, Some(Value::from(3, 8)));
+    }
+
+    #[test]
+    fn width_mismatch_is_a_diff() {
+        let expected = mem_map(vec![("mem", vec![Value::from(1, 8)])]);
+        let actual = mem_map(vec![("mem", vec![Value::from(1, 16)])]);
+        assert_eq!(expected.diff(&actual).len(), 1);
+    }
+
+    #[test]
+    fn missing_entries() {
+        let expected = mem_map(vec![
+            ("a", vec![Value::from(1, 4), Value::from(2, 4)]),
+            ("b", vec![Value::from(3, 4)]),
+        ]);
+        let actual = mem_map(vec![("a", vec![Value::from(1, 4)])]);
+        let diff = expected.diff(&actual);
+        assert_eq!(diff.len(), 2);
+        assert_eq!((diff[0].cell, diff[0].index), (Id::from("a"), 1));
+        assert_eq!(diff[0].actual, None);
+        assert_eq!((diff[1].cell, diff[1].index), (Id::from("b"), 0));
+        assert_eq!(diff[1].expected, Some(Value::from(3, 4)));
+    }
+
+    #[test]
+    fn changes_skip_one_sided_entries() {
+        let old = mem_map(vec![
+            ("a", vec![Value::from(1, 4), Value::from(2, 4)]),
+            ("b", vec![Value::from(3, 4)]),
+        ]);
+        let new = mem_map(vec![
+            ("a", vec![Value::from(5, 4)]),
+            ("c", vec![Value::from(0, 4)]),
+        ]);
+        assert_eq!(
+            old.changes(&new),
+            vec![(Id::from("a"), 0, Value::from(1, 4), Value::from(5, 4))]
+        );
+    }
+}
+
+#[cfg(test)]
+mod inflate_tests {
+    use crate::errors::{InterpreterError, InterpreterResult};
+    use crate::utils::{MemoryLayout, MemoryMap};
+    use crate::values::Value;
+    use calyx_ir::Id;
+    use std::collections::HashMap;
+    use std::path::PathBuf;
+
+    fn temp_file(name: &str, contents: &[u8]) -> PathBuf {
+        let path = std::env::temp_dir()
+            .join(format!("cider_{name}_{}", std::process::id()));
+        std::fs::write(&path, contents).unwrap();
+        path
+    }
+
+    #[test]
+    fn malformed_file_is_an_error() {
+        let path = temp_file("bad_mem.json", br#"{ "mem": [1, 2"#);
+
+        let res = MemoryMap::inflate_map(&Some(path.clone()));
+        std::fs::remove_file(&path).unwrap();
+
+        match &*res.unwrap_err() {
+            InterpreterError::InvalidMemoryFile { path: p, .. } => {
+                assert_eq!(p, &path)
+            }
+            other => panic!("expected a memory file error, got {other}"),
+        }
+    }
+
+    #[test]
+    fn json_bytes() {
+        let map = MemoryMap::from_json_bytes(
+            br#"{ "mem": [{ "value": 1, "width": 4 }, { "value": 2, "width": 4 }] }"#,
+        )
+        .unwrap();
+        assert_eq!(
+            map[&Id::from("mem")],
+            vec![Value::from(1, 4), Value::from(2, 4)]
+        );
+        // bare numbers do not say how wide they are
+        assert!(MemoryMap::from_json_bytes(br#"{ "mem": [1, 2] }"#).is_err());
+
+        match &*MemoryMap::from_json_bytes(b"{ \"mem\": [1, 2").unwrap_err() {
+            InterpreterError::InvalidMemoryData(_) => {}
+            other => panic!("expected a memory data error, got {other}"),
+        }
+    }
+
+    #[test]
+    fn binary_layout() {
+        let path =
+            temp_file("mem.bin", &[0x01, 0x02, 0x34, 0x02, 0xff, 0x0f, 0x07]);
+        let layout = [
+            MemoryLayout {
+                name: "a".into(),
+                width: 8,
+                count: 2,
+            },
+            MemoryLayout {
+                name: "b".into(),
+                width: 12,
+                count: 2,
+            },
+            MemoryLayout {
+                name: "c".into(),
+                width: 3,
+                count: 1,
+            },
+        ];
+
+        let map = MemoryMap::inflate_binary(&path, &layout);
+        std::fs::remove_file(&path).unwrap();
+        let map = map.unwrap();
+
+        assert_eq!(
+            map[&Id::from("a")],
+            vec![Value::from(1, 8), Value::from(2, 8)]
+        );
+        assert_eq!(
+            map[&Id::from("b")],
+            vec![Value::from(0x234, 12), Value::from(0xfff, 12)]
+        );
+        assert_eq!(map[&Id::from("c")], vec![Value::from(7, 3)]);
+    }
+
+    #[test]
+    fn binary_length_mismatch() {
+        let path = temp_file("short_mem.bin", &[0x01, 0x02, 0x03]);
+        let layout = [MemoryLayout {
+            name: "a".into(),
+            width: 16,
+            count: 2,
+        }];
+
+        let res = MemoryMap::inflate_binary(&path, &layout);
+        std::fs::remove_file(&path).unwrap();
+
+        match &*res.unwrap_err() {
+            InterpreterError::BinaryMemoryLength {
+                expected, actual, ..
+            } => assert_eq!((*expected, *actual), (4, 3)),
+            other => panic!("expected a length error, got {other}"),
+        }
+    }
+
+    #[test]
+    fn dump_round_trip() {
+        let original: MemoryMap = [
+            (
+                Id::from("mem"),
+                vec![Value::from(5, 3), Value::from(0, 3), Value::from(7, 3)],
+            ),
+            (Id::from("reg"), vec![Value::from(0xabcd, 16)]),
+            (Id::from("wide"), vec![Value::from(1_u128 << 90, 100)]),
+        ]
+        .into_iter()
+        .collect::<HashMap<_, _>>()
+        .into();
+
+        let first = temp_file("dump_a.json", b"");
+        let second = temp_file("dump_b.json", b"");
+
+        original.dump_to_json(&first).unwrap();
+        let loaded = MemoryMap::inflate_map(&Some(first.clone()))
+            .unwrap()
+            .unwrap();
+        loaded.dump_to_json(&second).unwrap();
+        let reloaded = MemoryMap::inflate_map(&Some(second.clone()))
+            .unwrap()
+            .unwrap();
+
+        let (a, b) = (
+            std::fs::read_to_string(&first).unwrap(),
+            std::fs::read_to_string(&second).unwrap(),
+        );
+        std::fs::remove_file(&first).unwrap();
+        std::fs::remove_file(&second).unwrap();
+
+        assert!(original.diff(&loaded).is_empty());
+        assert!(loaded.diff(&reloaded).is_empty());
+        assert_eq!(a, b);
+    }
+
+    fn load_json(name: &str, contents: &str) -> InterpreterResult<MemoryMap> {
+        let path = temp_file(name, contents.as_bytes());
+        let res = MemoryMap::inflate_map(&Some(path.clone()));
+        std::fs::remove_file(&path).unwrap();
+        res.map(Option::unwrap)
+    }
+
+    #[test]
+    fn signed_fixed_point() {
+        let map = load_json(
+            "sfp_mem.json",
+            r#"{ "mem": {
+                "format": { "is_signed": true, "int_width": 4, "frac_width": 4 },
+                "data": [-1.5, 2.25, -8.0, 0.0]
+            } }"#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            map[&Id::from("mem")],
+            vec![
+                Value::from(-24_i64, 8),
+                Value::from(36, 8),
+                Value::from(-128_i64, 8),
+                Value::from(0, 8)
+            ]
+        );
+    }
+
+    #[test]
+    fn fixed_point_rounds_to_nearest() {
+        let map = load_json(
+            "round_mem.json",
+            r#"{
+                "unsigned": {
+                    "format": { "is_signed": false, "int_width": 4, "frac_width": 2 },
+                    "data": [0.3, 0.375, 1.125]
+                },
+                "signed": {
+                    "format": { "is_signed": true, "int_width": 4, "frac_width": 2 },
+                    "data": [-0.3, -0.375]
+                }
+            }"#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            map[&Id::from("unsigned")],
+            vec![Value::from(1, 6), Value::from(2, 6), Value::from(5, 6)]
+        );
+        assert_eq!(
+            map[&Id::from("signed")],
+            vec![Value::from(-1_i64, 6), Value::from(-2_i64, 6)]
+        );
+    }
+
+    #[test]
+    fn fixed_point_out_of_range() {
+        let unsigned_negative = load_json(
+            "neg_ufp_mem.json",
+            r#"{ "mem": {
+                "format": { "is_signed": false, "int_width": 4, "frac_width": 4 },
+                "data": [-0.5]
+            } }"#,
+        );
+        let too_large = load_json(
+            "big_sfp_mem.json",
+            r#"{ "mem": {
+                "format": { "is_signed": true, "int_width": 4, "frac_width": 4 },
+                "data": [8.0]
+            } }"#,
+        );
+
+        for res in [unsigned_negative, too_large] {
+            assert!(matches!(
+                &*res.unwrap_err(),
+                InterpreterError::InvalidMemoryFile { .. }
+            ));
+        }
+    }
+
+    #[test]
+    fn sized_values() {
+        let map = load_json(
+            "sized_vals_mem.json",
+            r#"{
+                "mem": [{ "value": 3, "width": 4 }, { "value": 15, "width": 4 }],
+                "reg": { "value": 1, "width": 1 }
+            }"#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            map[&Id::from("mem")],
+            vec![Value::from(3, 4), Value::from(15, 4)]
+        );
+        assert_eq!(map[&Id::from("reg")], vec![Value::from(1, 1)]);
+
+        let too_wide = load_json(
+            "wide_val_mem.json",
+            r#"{ "mem": [{ "value": 16, "width": 4 }] }"#,
+        );
+        assert!(matches!(
+            &*too_wide.unwrap_err(),
+            InterpreterError::InvalidMemoryFile { .. }
+        ));
+
+        // an entry-wide width narrows values which fit but never truncates
+        let narrowed = load_json(
+            "narrowed_mem.json",
+            r#"{ "mem": { "width": 3, "data": [{ "value": 5, "width": 8 }] } }"#,
+        );
+        assert_eq!(
+            narrowed.unwrap()[&Id::from("mem")],
+            vec![Value::from(5, 3)]
+        );
+        let overflow = load_json(
+            "overflow_mem.json",
+            r#"{ "mem": { "width": 3, "data": [{ "value": 9, "width": 8 }] } }"#,
+        );
+        assert!(matches!(
+            &*overflow.unwrap_err(),
+            InterpreterError::InvalidMemoryFile { .. }
+        ));
+    }
+
+    #[test]
+    fn dump_rejects_mixed_widths() {
+        let map: MemoryMap =
+            [(Id::from("mem"), vec![Value::from(1, 4), Value::from(2, 8)])]
+                .into_iter()
+                .collect::<HashMap<_, _>>()
+                .into();
+
+        let path = temp_file("mixed_dump.json", b"");
+        let res = map.dump_to_json(&path);
+        std::fs::remove_file(&path).unwrap();
+        match &*res.unwrap_err() {
+            InterpreterError::MixedMemoryWidths {
+                index,
+                expected,
+                given,
+                ..
+            } => assert_eq!((*index, *expected, *given), (1, 4, 8)),
+            other => panic!("expected mixed widths, got {other}"),
+        }
+    }
+
+    #[test]
+    fn nested_arrays() {
+        let v = |n| format!(r#"{{ "value": {n}, "width": 4 }}"#);
+        let contents = format!(
+            r#"{{
+                "flat": [{}, {}],
+                "grid": [[{}, {}], [{}, {}], [{}, {}]]
+            }}"#,
+            v(1),
+            v(2),
+            v(0),
+            v(1),
+            v(2),
+            v(3),
+            v(4),
+            v(5)
+        );
+        let map = load_json("nested_mem.json", &contents).unwrap();
+
+        let (flat, grid) = (Id::from("flat"), Id::from("grid"));
+        assert_eq!(map.dimensions(&flat), Some(vec![2]));
+        assert_eq!(map.dimensions(&grid), Some(vec![3, 2]));
+        assert_eq!(map.dimensions(&Id::from("missing")), None);
+        assert_eq!(
+            map[&grid],
+            (0..6).map(|n| Value::from(n, 4)).collect::<Vec<_>>()
+        );
+
+        let dumped = temp_file("nested_dump.json", b"");
+        map.dump_to_json(&dumped).unwrap();
+        let reloaded = MemoryMap::inflate_map(&Some(dumped.clone()))
+            .unwrap()
+            .unwrap();
+        std::fs::remove_file(&dumped).unwrap();
+        assert_eq!(reloaded.dimensions(&grid), Some(vec![3, 2]));
+        assert!(map.diff(&reloaded).is_empty());
+
+        // a resized memory no longer has the shape it was loaded with
+        let mut resized = reloaded;
+        resized.get_mut(&grid).unwrap().pop();
+        assert_eq!(resized.dimensions(&grid), Some(vec![5]));
+        let dumped = temp_file("resized_dump.json", b"");
+        resized.dump_to_json(&dumped).unwrap();
+        let flat_reload = MemoryMap::inflate_map(&Some(dumped.clone()))
+            .unwrap()
+            .unwrap();
+        std::fs::remove_file(&dumped).unwrap();
+        assert_eq!(flat_reload.dimensions(&grid), Some(vec![5]));
+        assert!(resized.diff(&flat_reload).is_empty());
+
+        let ragged = load_json(
+            "ragged_mem.json",
+            &format!(r#"{{ "mem": [[{}, {}], [{}]] }}"#, v(0), v(1), v(2)),
+        );
+        assert!(matches!(
+            &*ragged.unwrap_err(),
+            InterpreterError::InvalidMemoryFile { .. }
+        ));
+    }
+}
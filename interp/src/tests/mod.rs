@@ -1,3 +1,11 @@
+mod assignments;
+mod builder;
+mod control;
+mod environment;
+mod guard;
+mod memory_map;
 mod primitives;
+mod program;
 mod stk_env;
+mod translator;
 mod values;
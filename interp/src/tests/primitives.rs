@@ -1643,3 +1643,21 @@ mod property_tests {
         }
     }
 }
+
+#[test]
+fn checked_bindings() {
+    use crate::errors::InterpreterError;
+    use crate::utils::construct_bindings_checked;
+
+    let binds =
+        construct_bindings_checked([("WIDTH", 32), ("SIZE", 4)]).unwrap();
+    assert_eq!(binds, vec![("WIDTH".into(), 32), ("SIZE".into(), 4)]);
+
+    let res = construct_bindings_checked([("WIDTH", 32), ("WIDTH", 8)]);
+    match &*res.unwrap_err() {
+        InterpreterError::DuplicateParameter(name) => {
+            assert_eq!(*name, ir::Id::from("WIDTH"))
+        }
+        other => panic!("expected a duplicate parameter error, got {other}"),
+    }
+}
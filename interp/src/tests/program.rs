@@ -0,0 +1,211 @@
+#[cfg(test)]
+mod program_tests {
+    use crate::configuration::Config;
+    use crate::environment::InterpreterState;
+    use crate::errors::InterpreterError;
+    use crate::interpreter::{ProgramInterpreter, StepResult};
+    use crate::interpreter_ir::{
+        Cell, Component, ComponentBuilder, Control, Guard, Port, Repeat,
+    };
+    use crate::tests::builder::helpers::{assign, enable};
+    use crate::utils::{ArcTex, StimulusMap};
+    use crate::values::Value;
+    use calyx_ir::{self as orig_ir, Canonical, Direction};
+    use std::sync::Arc;
+
+    /// `main(in: 8) -> (out: 8)` which writes `in` into the register `r`
+    /// `repeats` times, with `out` wired to `r.out`
+    fn program(repeats: u64) -> (Arc<Component>, ArcTex<Port>, ArcTex<Cell>) {
+        let mut builder = ComponentBuilder::new("main");
+        let sig_in = builder.add_input("in", 8);
+        let go = builder.add_input("go", 1);
+        go.write().attributes.insert(orig_ir::NumAttr::Go, 1);
+        let out = builder.add_output("out", 8);
+        let done = builder.add_output("done", 1);
+        done.write().attributes.insert(orig_ir::NumAttr::Done, 1);
+
+        let reg = builder.add_cell(
+            "r",
+            orig_ir::CellType::Primitive {
+                name: "std_reg".into(),
+                param_binding: Box::new(vec![("WIDTH".into(), 8)]),
+                is_comb: false,
+                latency: None,
+            },
+            &[
+                ("in", 8, Direction::Input),
+                ("write_en", 1, Direction::Input),
+                ("reset", 1, Direction::Input),
+                ("out", 8, Direction::Output),
+                ("done", 1, Direction::Output),
+            ],
+        );
+        let high = builder.add_cell(
+            "_1_1",
+            orig_ir::CellType::Constant { val: 1, width: 1 },
+            &[("out", 1, Direction::Output)],
+        );
+
+        let write = builder.add_group("write");
+        let hole = write.read().get("done");
+        write.write().assignments = vec![
+            assign(&reg.read().get("in"), &sig_in, Guard::True),
+            assign(
+                &reg.read().get("write_en"),
+                &high.read().get("out"),
+                Guard::True,
+            ),
+            assign(&hole, &reg.read().get("done"), Guard::True),
+        ];
+        builder.add_continuous_assignment(assign(
+            &out,
+            &reg.read().get("out"),
+            Guard::True,
+        ));
+        builder.set_control(Control::Repeat(
+            Repeat {
+                body: enable(write),
+                num_repeats: repeats,
+                attributes: Default::default(),
+            }
+            .into(),
+        ));
+
+        (Arc::new(builder.build().unwrap()), sig_in, reg)
+    }
+
+    fn start(
+        comp: &Arc<Component>,
+        stimulus: Option<StimulusMap>,
+    ) -> ProgramInterpreter {
+        let ctx = Arc::new(vec![Arc::clone(comp)]);
+        let config = Config {
+            stimulus: stimulus.map(Arc::new),
+            ..Default::default()
+        };
+        let env =
+            InterpreterState::init_top_level(&ctx, comp, &mut None, &config)
+                .unwrap();
+        ProgramInterpreter::new(env, comp)
+    }
+
+    fn main_port(name: &str) -> Canonical {
+        Canonical("main".into(), name.into())
+    }
+
+    #[test]
+    fn stimulus_validate() {
+        let (comp, _, _) = program(1);
+
+        let mut stimulus = StimulusMap::new();
+        stimulus.insert(0, main_port("in"), Value::from(1, 8));
+        stimulus.insert(3, main_port("in"), Value::from(2, 8));
+        assert!(stimulus.validate(&comp).is_ok());
+
+        stimulus.insert(5, main_port("in"), Value::from(2, 4));
+        match &*stimulus.validate(&comp).unwrap_err() {
+            InterpreterError::StimulusWidthMismatch {
+                port,
+                expected,
+                given,
+            } => {
+                assert_eq!(port, "main.in");
+                assert_eq!(*expected, 8);
+                assert_eq!(*given, 4);
+            }
+            other => panic!("expected a width mismatch, got {other}"),
+        }
+
+        // outputs, missing ports and other components are all unknown
+        for port in [
+            main_port("out"),
+            main_port("missing"),
+            Canonical("other".into(), "in".into()),
+        ] {
+            let name = port.to_string();
+            let mut stimulus = StimulusMap::new();
+            stimulus.insert(0, port, Value::from(1, 8));
+            match &*stimulus.validate(&comp).unwrap_err() {
+                InterpreterError::UnknownStimulusPort(given) => {
+                    assert_eq!(*given, name)
+                }
+                other => panic!("expected an unknown port, got {other}"),
+            }
+        }
+    }
+
+    #[test]
+    fn stimulus_persists_until_overridden() {
+        let (comp, sig_in, reg) = program(10);
+
+        let mut stimulus = StimulusMap::new();
+        stimulus.insert(0, main_port("in"), Value::from(1, 8));
+        stimulus.insert(2, main_port("in"), Value::from(5, 8));
+        stimulus.insert(4, main_port("in"), Value::from(9, 8));
+        let mut prog = start(&comp, Some(stimulus));
+
+        // the value on `in` after each cycle, i.e. the latest stimulus at or
+        // before the cycle just taken
+        for expected in [1, 1, 5, 5, 9, 9] {
+            prog.step().unwrap();
+            assert_eq!(
+                prog.state().lookup(&*sig_in.read()),
+                &Value::from(expected, 8)
+            );
+        }
+
+        prog.run().unwrap();
+        let env = prog.finish().unwrap();
+        let out = reg.read().get("out");
+        assert_eq!(env.get_from_port(&*out.read()), &Value::from(9, 8));
+    }
+
+    #[test]
+    fn unknown_stimulus_fails_step() {
+        let (comp, _, _) = program(1);
+
+        let mut stimulus = StimulusMap::new();
+        stimulus.insert(0, main_port("missing"), Value::from(1, 8));
+        let mut prog = start(&comp, Some(stimulus));
+
+        match &*prog.step().unwrap_err() {
+            InterpreterError::UnknownStimulusPort(port) => {
+                assert_eq!(port, "main.missing")
+            }
+            other => panic!("expected an unknown port, got {other}"),
+        }
+    }
+
+    #[test]
+    fn step_counts_cycles() {
+        let (comp, _, _) = program(3);
+        let mut prog = start(&comp, None);
+        assert_eq!(prog.cycles(), 0);
+        assert!(!prog.is_done());
+
+        // every step takes exactly one cycle until the program finishes
+        let mut result = StepResult::Running;
+        while result == StepResult::Running {
+            let before = prog.cycles();
+            result = prog.step().unwrap();
+            assert_eq!(prog.cycles(), before + 1);
+            assert_eq!(prog.is_done(), result == StepResult::Done);
+        }
+        // each of the three writes takes at least a cycle
+        let total = prog.cycles();
+        assert!(total >= 3);
+
+        // stepping a finished program does nothing
+        assert_eq!(prog.step().unwrap(), StepResult::Done);
+        assert_eq!(prog.step_n(5).unwrap(), StepResult::Done);
+        assert_eq!(prog.cycles(), total);
+        assert!(prog.finish().is_ok());
+
+        // step_n stops early once the program finishes
+        let mut prog = start(&comp, None);
+        assert_eq!(prog.step_n(2).unwrap(), StepResult::Running);
+        assert_eq!(prog.cycles(), 2);
+        assert_eq!(prog.step_n(total * 2).unwrap(), StepResult::Done);
+        assert_eq!(prog.cycles(), total);
+    }
+}
@@ -0,0 +1,135 @@
+#[cfg(test)]
+mod translator_tests {
+    use crate::interpreter_ir::{TranslationMap, TranslationStats};
+    use calyx_ir as orig_ir;
+    use std::sync::Arc;
+
+    #[test]
+    fn repeated_lookups_are_hits() {
+        let mut tm = TranslationMap::new();
+        let a = orig_ir::rrc(orig_ir::Group::new("a".into()));
+        let b = orig_ir::rrc(orig_ir::Group::new("b".into()));
+
+        let first = tm.get_group(&a);
+        let second = tm.get_group(&a);
+        tm.get_group(&b);
+
+        assert!(Arc::ptr_eq(&first, &second));
+        assert_eq!(
+            tm.stats(),
+            TranslationStats {
+                groups: 2,
+                hits: 1,
+                misses: 2,
+                ..Default::default()
+            }
+        );
+    }
+
+    /// A constant cell with one output port per name
+    fn cell_with_ports(names: &[&str]) -> orig_ir::RRC<orig_ir::Cell> {
+        let cell = orig_ir::rrc(orig_ir::Cell::new(
+            "c".into(),
+            orig_ir::CellType::Constant { val: 0, width: 1 },
+        ));
+        for name in names {
+            let port = orig_ir::rrc(orig_ir::Port {
+                name: (*name).into(),
+                width: 1,
+                direction: orig_ir::Direction::Output,
+                parent: orig_ir::PortParent::Cell(orig_ir::WRC::from(&cell)),
+                attributes: Default::default(),
+            });
+            cell.borrow_mut().ports.push(port);
+        }
+        cell
+    }
+
+    #[test]
+    fn uids_are_deterministic() {
+        let cell = cell_with_ports(&["a", "b"]);
+        let uids = || {
+            let translated = TranslationMap::new().get_cell(&cell);
+            let translated = translated.read();
+            let ports = translated.ports().iter().map(|p| p.read().uid());
+            std::iter::once(translated.uid())
+                .chain(ports)
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(uids(), vec![0, 1, 2]);
+        assert_eq!(uids(), uids());
+    }
+
+    /// A component with one cell and `groups` groups, each of which drives
+    /// `c.a` from `c.b` twice under the guard `c.a`
+    fn component_with_groups(groups: usize) -> orig_ir::Component {
+        let mut comp =
+            orig_ir::Component::new("main", vec![], false, false, None);
+        let cell = cell_with_ports(&["a", "b"]);
+        let (a, b) = {
+            let cell = cell.borrow();
+            (cell.get("a"), cell.get("b"))
+        };
+        comp.cells.add(cell);
+        for i in 0..groups {
+            let group =
+                orig_ir::rrc(orig_ir::Group::new(format!("g{i}").into()));
+            for _ in 0..2 {
+                group.borrow_mut().assignments.push(orig_ir::Assignment {
+                    dst: a.clone(),
+                    src: b.clone(),
+                    guard: orig_ir::Guard::port(a.clone()).into(),
+                    attributes: Default::default(),
+                });
+            }
+            comp.groups.add(group);
+        }
+        comp
+    }
+
+    /// The ids of every cell of `comp` and of their ports, in order
+    fn uids_of(comp: &crate::interpreter_ir::Component) -> Vec<u32> {
+        std::iter::once(&comp.signature)
+            .chain(comp.cells.iter())
+            .flat_map(|cell| {
+                let cell = cell.read();
+                let ports = cell.ports().iter().map(|p| p.read().uid());
+                std::iter::once(cell.uid()).chain(ports).collect::<Vec<_>>()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn parallel_matches_sequential() {
+        let comps = vec![component_with_groups(8), component_with_groups(3)];
+        let mut sequential_map = TranslationMap::new();
+        let sequential = sequential_map.translate_components(&comps);
+        let mut parallel_map = TranslationMap::new();
+        let parallel = parallel_map.translate_components_parallel(&comps);
+
+        for (seq, par) in sequential.iter().zip(parallel.iter()) {
+            assert_eq!(seq.structural_hash(), par.structural_hash());
+            assert_eq!(uids_of(seq), uids_of(par));
+        }
+        assert_eq!(sequential_map.stats(), parallel_map.stats());
+
+        // each port is translated once, and every group writes to it
+        let a = parallel[0].cells.iter().next().unwrap().read().get("a");
+        assert!(parallel[0].groups.iter().all(|g| g
+            .read()
+            .assignments
+            .iter()
+            .all(|assign| Arc::ptr_eq(&assign.dst, &a))));
+
+        // identical guards are still interned across groups
+        let guards = parallel[0]
+            .groups
+            .iter()
+            .flat_map(|g| g.read().assignments.clone())
+            .map(|assign| assign.guard)
+            .collect::<Vec<_>>();
+        assert_eq!(guards.len(), 16);
+        assert!(guards.iter().all(|g| Arc::ptr_eq(g, &guards[0])));
+    }
+}
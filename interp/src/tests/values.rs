@@ -30,6 +30,148 @@ mod val_test {
         let v_15_4 = Value::from(15, 4);
         assert_eq!(v_15_4.as_u64(), v_15_4.ext(8).as_u64());
     }
+
+    #[test]
+    fn from_bits() {
+        let v = Value::from_bits(8, &[true, false, true, true]).unwrap();
+        assert_eq!(v, Value::from(13, 8));
+        assert!(Value::from_bits(2, &[false, false, true]).is_err());
+        // high zero bits beyond the width are fine
+        let v = Value::from_bits(2, &[true, false, false]).unwrap();
+        assert_eq!(v, Value::from(1, 2));
+    }
+
+    #[test]
+    fn bits_round_trip() {
+        // values are truncated to the width, so every width starts 1, 0, 1
+        for width in [3, 7, 13, 63, 65, 100] {
+            let v = Value::from(0b1011_0110_1101_u64, width);
+            let bits = v.bits().collect::<Vec<_>>();
+            assert_eq!(bits.len() as u64, width);
+            assert_eq!(bits[..3], [true, false, true]);
+            assert_eq!(v.bits().collect::<Value>(), v);
+        }
+        assert_eq!(std::iter::empty().collect::<Value>().width(), 0);
+    }
+
+    #[test]
+    fn from_bytes_le() {
+        let v = Value::from_bytes_le(16, &[0x34, 0x12]).unwrap();
+        assert_eq!(v, Value::from(0x1234, 16));
+        let v = Value::from_bytes_le(20, &[0x34, 0x12]).unwrap();
+        assert_eq!(v, Value::from(0x1234, 20));
+        assert!(Value::from_bytes_le(12, &[0x34, 0x12]).is_err());
+    }
+
+    #[test]
+    fn bit_range_single_bit() {
+        let v = Value::from(0b1010, 4);
+        assert_eq!(v.bit_range(0, 1).unwrap(), Value::from(0, 1));
+        assert_eq!(v.bit_range(3, 4).unwrap(), Value::from(1, 1));
+    }
+
+    #[test]
+    fn bit_range_full_width() {
+        let v = Value::from(0xbeef, 16);
+        assert_eq!(v.bit_range(0, 16).unwrap(), v);
+        assert_eq!(v.bit_range(4, 12).unwrap(), Value::from(0xee, 8));
+        assert_eq!(v.bit_range(5, 5).unwrap().width(), 0);
+    }
+
+    #[test]
+    fn bit_range_out_of_range() {
+        let v = Value::from(3, 4);
+        assert!(v.bit_range(0, 5).is_err());
+        assert!(v.bit_range(4, 5).is_err());
+        assert!(v.bit_range(3, 2).is_err());
+    }
+
+    #[test]
+    fn signed_width_one() {
+        assert_eq!(Value::from(1, 1).as_i64(), -1);
+        assert_eq!(Value::from(0, 1).as_i64(), 0);
+        assert_eq!(Value::from_signed(-1, 1), Value::from(1, 1));
+    }
+
+    #[test]
+    fn signed_most_negative() {
+        assert_eq!(Value::from_signed(-8, 4).as_i64(), -8);
+        assert_eq!(Value::from(0b1000, 4).as_i64(), -8);
+        let min = Value::from_signed(i64::MIN, 64);
+        assert_eq!(min.try_as_i64(), Some(i64::MIN));
+    }
+
+    #[test]
+    fn signed_wide_values() {
+        let v = Value::from_signed(-5, 100);
+        assert_eq!(v.width(), 100);
+        assert_eq!(v.try_as_i64(), Some(-5));
+        assert_eq!(v.as_i128(), -5);
+        // a positive value that needs more than 64 bits
+        let big = Value::from(1_u128 << 70, 100);
+        assert_eq!(big.try_as_i64(), None);
+    }
+
+    #[test]
+    fn concat_places_self_high() {
+        let v = Value::from(0xa, 4).concat(&Value::from(0x5, 4)).unwrap();
+        assert_eq!(v, Value::from(0xa5, 8));
+    }
+
+    #[test]
+    fn concat_slice_round_trip() {
+        let high = Value::from(0b101, 3);
+        let low = Value::from(0x1234, 16);
+        let joined = high.concat(&low).unwrap();
+        assert_eq!(joined.width(), 19);
+        assert_eq!(joined.bit_range(0, 16).unwrap(), low);
+        assert_eq!(joined.bit_range(16, 19).unwrap(), high);
+    }
+
+    #[test]
+    fn bitwise_eq_requires_matching_width() {
+        let a = Value::from(5, 4);
+        assert!(a.bitwise_eq(&Value::from(5, 4)));
+        assert!(!a.bitwise_eq(&Value::from(6, 4)));
+        assert!(!a.bitwise_eq(&Value::from(5, 8)));
+        assert_ne!(a, Value::from(5, 8));
+    }
+
+    #[test]
+    fn shifts_keep_width() {
+        let v = Value::from(0b1011, 4);
+        assert_eq!(v.shift_left(1), Value::from(0b0110, 4));
+        assert_eq!(v.shift_right(1), Value::from(0b0101, 4));
+        assert_eq!(v.shift_left(0), v);
+        assert_eq!(v.shift_right(0), v);
+        assert_eq!(v.shift_left(4), Value::zeroes(4));
+        assert_eq!(v.shift_right(4), Value::zeroes(4));
+        assert_eq!(v.shift_left(9), Value::zeroes(4));
+    }
+
+    #[test]
+    fn bitwise_ops() {
+        let (l, r) = (Value::from(0b1100, 4), Value::from(0b1010, 4));
+        assert_eq!(l.bitand(&r).unwrap(), Value::from(0b1000, 4));
+        assert_eq!(l.bitor(&r).unwrap(), Value::from(0b1110, 4));
+        assert_eq!(l.bitxor(&r).unwrap(), Value::from(0b0110, 4));
+        assert!(l.bitand(&Value::from(1, 5)).is_err());
+    }
+
+    #[test]
+    fn checked_resizing() {
+        let v = Value::from(0b1011, 4);
+        assert_eq!(v.try_truncate(4).unwrap(), v);
+        assert_eq!(v.zero_extend(4).unwrap(), v);
+        assert_eq!(v.sign_extend(8).unwrap(), Value::from(-5_i64, 8));
+        assert_eq!(
+            Value::from(0b0011, 4).sign_extend(8).unwrap(),
+            Value::from(3, 8)
+        );
+        assert!(v.try_truncate(5).is_err());
+        assert!(v.zero_extend(3).is_err());
+        assert!(v.sign_extend(3).is_err());
+    }
 }
 
 #[cfg(test)]
@@ -325,3 +467,66 @@ mod property_tests {
         }
     }
 }
+
+#[cfg(test)]
+mod format_value_tests {
+    use crate::errors::InterpreterError;
+    use crate::interpreter::format_value;
+    use crate::utils::PrintCode;
+    use crate::values::Value;
+
+    #[test]
+    fn integer_codes() {
+        let val = Value::from(0b1110_u64, 4);
+        assert_eq!(format_value(&val, PrintCode::Unsigned), "14");
+        assert_eq!(format_value(&val, PrintCode::Signed), "-2");
+        assert_eq!(format_value(&val, PrintCode::Binary), "[1110]");
+        assert_eq!(format_value(&val, PrintCode::Hex), "0xe");
+    }
+
+    #[test]
+    fn fixed_point_codes() {
+        let val = Value::from(0b0101_u64, 4);
+        assert_eq!(format_value(&val, PrintCode::UFixed(2)), "5/4");
+        assert_eq!(format_value(&val, PrintCode::UFixed(0)), "5");
+
+        let neg = Value::from(0b1110_u64, 4);
+        assert_eq!(format_value(&neg, PrintCode::SFixed(1)), "-1");
+        assert_eq!(format_value(&neg, PrintCode::UFixed(1)), "7");
+    }
+
+    #[test]
+    fn print_code_round_trip() {
+        let codes = [
+            PrintCode::Binary,
+            PrintCode::Unsigned,
+            PrintCode::Signed,
+            PrintCode::Hex,
+            PrintCode::UFixed(3),
+            PrintCode::SFixed(12),
+        ];
+        for code in codes {
+            assert_eq!(code.to_string().parse::<PrintCode>().unwrap(), code);
+        }
+    }
+
+    #[test]
+    fn print_code_spellings() {
+        let parse = |s: &str| s.parse::<PrintCode>().unwrap();
+        assert_eq!(parse("unsigned"), PrintCode::Unsigned);
+        assert_eq!(parse("S"), PrintCode::Signed);
+        assert_eq!(parse("binary"), PrintCode::Binary);
+        assert_eq!(parse("x"), PrintCode::Hex);
+        assert_eq!(parse("u.4"), PrintCode::UFixed(4));
+        assert_eq!(parse("\\s.2"), PrintCode::SFixed(2));
+
+        for bad in ["", "octal", "u.", "s.x", "\\"] {
+            match &*bad.parse::<PrintCode>().unwrap_err() {
+                InterpreterError::UnknownPrintCode(given) => {
+                    assert_eq!(given, bad)
+                }
+                other => panic!("expected an unknown print code, got {other}"),
+            }
+        }
+    }
+}
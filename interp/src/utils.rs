@@ -1,15 +1,19 @@
+use crate::errors::{InterpreterError, InterpreterResult};
 use crate::interpreter_ir::*;
 use crate::values::Value;
-use calyx_ir::{Binding, Id, Nothing, RRC};
+use calyx_ir::{Binding, Canonical, Direction, Id, Nothing, RRC};
 use parking_lot::{RwLock, RwLockReadGuard};
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 use std::fs;
 use std::hash::{Hash, Hasher};
 use std::ops::{Deref, DerefMut};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::rc::Rc;
 use std::{cell::Ref, sync::Arc};
-use std::{collections::HashMap, sync::Weak};
+use std::{
+    collections::{BTreeMap, HashMap},
+    sync::Weak,
+};
 
 pub use crate::debugger::PrintCode;
 /// A wrapper to enable hashing of assignments by their destination port.
@@ -48,10 +52,195 @@ impl<'a> PortAssignment<'a> {
 }
 
 /// A map representing all the identifiers and its associated values in a
-/// Futil program.
-#[derive(Debug, Deserialize)]
-#[serde(transparent)]
-pub struct MemoryMap(HashMap<Id, Vec<Value>>);
+/// Futil program. Multi-dimensional memories are stored flattened in row-major
+/// order, with their dimensions recorded separately.
+#[derive(Debug)]
+pub struct MemoryMap {
+    data: HashMap<Id, Vec<Value>>,
+    /// The shape of each memory loaded from nested arrays
+    dims: HashMap<Id, Vec<usize>>,
+}
+
+/// A single entry in a memory file. Registers may be given a lone value rather
+/// than a one element array, and multi-dimensional memories may be given as
+/// nested arrays. Values on their own only record a whole number of bytes, so
+/// an entry may also give the exact width of its elements, which every
+/// element must fit in. Fixed point
+/// memories instead give their format and a list of numbers.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum MemoryEntry {
+    Array(Vec<NestedValues>),
+    Scalar(Value),
+    Sized {
+        width: u64,
+        data: Vec<NestedValues>,
+    },
+    FixedPoint {
+        format: FixedPointFormat,
+        data: Vec<f64>,
+    },
+}
+
+/// An element of a possibly nested array of values
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum NestedValues {
+    Leaf(Value),
+    Rows(Vec<NestedValues>),
+}
+
+/// Flattens nested arrays in row-major order, returning the values along with
+/// the length of each dimension. Errors if the arrays are not rectangular.
+fn flatten_nested(
+    items: Vec<NestedValues>,
+) -> Result<(Vec<Value>, Vec<usize>), String> {
+    let len = items.len();
+    let mut values = vec![];
+    let mut inner: Option<Vec<usize>> = None;
+    for item in items {
+        let dims = match item {
+            NestedValues::Leaf(v) => {
+                values.push(v);
+                vec![]
+            }
+            NestedValues::Rows(rows) => {
+                let (vals, dims) = flatten_nested(rows)?;
+                values.extend(vals);
+                dims
+            }
+        };
+        match &inner {
+            Some(prev) if *prev != dims => {
+                return Err("nested arrays must be rectangular".to_string())
+            }
+            Some(_) => {}
+            None => inner = Some(dims),
+        }
+    }
+
+    let mut dims = vec![len];
+    dims.extend(inner.unwrap_or_default());
+    Ok((values, dims))
+}
+
+/// Re-nests flattened values into arrays with the given dimensions
+fn nest_values(values: &[Value], dims: &[usize]) -> serde_json::Value {
+    match dims {
+        [] | [_] => serde_json::to_value(values).unwrap(),
+        [_, rest @ ..] => {
+            let row_len = rest.iter().product::<usize>().max(1);
+            values
+                .chunks(row_len)
+                .map(|row| nest_values(row, rest))
+                .collect()
+        }
+    }
+}
+
+/// The layout of the elements of a fixed point memory
+#[derive(Deserialize, Debug, Clone, Copy)]
+struct FixedPointFormat {
+    is_signed: bool,
+    int_width: u64,
+    frac_width: u64,
+}
+
+impl FixedPointFormat {
+    /// Converts `num` into the bit pattern of this format, rounding to the
+    /// nearest representable value with ties going away from zero.
+    fn to_value(self, num: f64) -> Result<Value, String> {
+        let width = self.int_width + self.frac_width;
+        if width == 0 || width > 64 {
+            return Err(format!(
+                "fixed point formats must be between 1 and 64 bits wide, got {width}"
+            ));
+        }
+
+        let scaled = (num * 2_f64.powi(self.frac_width as i32)).round();
+        // bounds are powers of two, so they convert to floats exactly
+        let (min, bound) = if self.is_signed {
+            (-(1_i128 << (width - 1)), 1_i128 << (width - 1))
+        } else {
+            (0, 1_i128 << width)
+        };
+
+        if !scaled.is_finite() || scaled < min as f64 || scaled >= bound as f64
+        {
+            return Err(format!(
+                "{num} does not fit in a fixed point number with {} integer and {} fractional bits",
+                self.int_width, self.frac_width
+            ));
+        }
+
+        Ok(Value::from(scaled as i128, width))
+    }
+}
+
+/// The form [MemoryMap::dump_to_json] writes each memory in
+#[derive(Serialize)]
+struct SizedMemoryEntry {
+    width: u64,
+    data: serde_json::Value,
+}
+
+impl<'de> Deserialize<'de> for MemoryMap {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let entries = HashMap::<Id, MemoryEntry>::deserialize(deserializer)?;
+        let mut map = MemoryMap::from(HashMap::new());
+        for (id, entry) in entries {
+            let err = |msg: String| -> D::Error {
+                serde::de::Error::custom(format!("memory {id}: {msg}"))
+            };
+            let (vals, dims) = match entry {
+                MemoryEntry::Array(vals) => {
+                    flatten_nested(vals).map_err(err)?
+                }
+                MemoryEntry::Scalar(val) => (vec![val], vec![1]),
+                MemoryEntry::Sized { width, data } => {
+                    let (vals, dims) = flatten_nested(data).map_err(err)?;
+                    let vals = vals
+                        .into_iter()
+                        .enumerate()
+                        .map(|(index, v)| {
+                            if !Value::unsigned_value_fits_in(
+                                v.bv_ref(),
+                                width as usize,
+                            ) {
+                                Err(format!(
+                                    "entry {index} does not fit in the width of {width}"
+                                ))
+                            } else if v.width() >= width {
+                                Ok(v.truncate(width as usize))
+                            } else {
+                                Ok(v.ext(width as usize))
+                            }
+                        })
+                        .collect::<Result<Vec<_>, _>>()
+                        .map_err(err)?;
+                    (vals, dims)
+                }
+                MemoryEntry::FixedPoint { format, data } => {
+                    let vals = data
+                        .into_iter()
+                        .map(|num| format.to_value(num))
+                        .collect::<Result<Vec<_>, _>>()
+                        .map_err(err)?;
+                    let len = vals.len();
+                    (vals, vec![len])
+                }
+            };
+            if dims.len() > 1 {
+                map.dims.insert(id, dims);
+            }
+            map.data.insert(id, vals);
+        }
+        Ok(map)
+    }
+}
 
 impl MemoryMap {
     pub fn inflate_map(
@@ -59,29 +248,369 @@ impl MemoryMap {
     ) -> crate::errors::InterpreterResult<Option<Self>> {
         if let Some(path) = path {
             let v = fs::read(path)?;
-            let file_contents = std::str::from_utf8(&v)?;
-            let map: MemoryMap = serde_json::from_str(file_contents).unwrap();
+            let map = Self::from_json_bytes(&v).map_err(|e| match &*e {
+                InterpreterError::InvalidMemoryData(msg) => {
+                    InterpreterError::InvalidMemoryFile {
+                        path: path.clone(),
+                        msg: msg.clone(),
+                    }
+                    .into()
+                }
+                _ => e,
+            })?;
             return Ok(Some(map));
         }
 
         Ok(None)
     }
+
+    /// Parses memory data in the JSON format read by [MemoryMap::inflate_map]
+    /// from a buffer rather than a file. Elements are given as base64
+    /// strings or as `{ "value": 5, "width": 4 }` objects; bare numbers carry
+    /// no width and are rejected.
+    pub fn from_json_bytes(bytes: &[u8]) -> InterpreterResult<Self> {
+        let contents = std::str::from_utf8(bytes)?;
+        serde_json::from_str(contents).map_err(|e| {
+            InterpreterError::InvalidMemoryData(e.to_string()).into()
+        })
+    }
+
+    /// Checks the loaded data against the memories declared in `comp`. Every
+    /// entry for a register or memory cell must supply exactly as many
+    /// values as the cell holds, and no value may set bits beyond the cell's
+    /// `WIDTH`. Entries naming other cells are ignored.
+    pub fn validate_against(&self, comp: &Component) -> InterpreterResult<()> {
+        for cell in comp.cells.iter() {
+            let cell = cell.read();
+            let Some(data) = self.get(&cell.name()) else {
+                continue;
+            };
+            let Some(size) = memory_size(&cell) else {
+                continue;
+            };
+            let width = cell.get_parameter("WIDTH").unwrap_or_default();
+
+            if data.len() as u64 != size {
+                return Err(InterpreterError::MemoryInitLength {
+                    cell: cell.name(),
+                    expected: size,
+                    given: data.len(),
+                }
+                .into());
+            }
+
+            if let Some(index) = data.iter().position(|v| {
+                !Value::unsigned_value_fits_in(v.bv_ref(), width as usize)
+            }) {
+                return Err(InterpreterError::MemoryInitOverflow {
+                    cell: cell.name(),
+                    index,
+                    width,
+                }
+                .into());
+            }
+        }
+        Ok(())
+    }
+
+    /// The length of each dimension of the memory `id`, outermost first. This
+    /// is the shape of the nested arrays it was loaded from, or just its
+    /// length if it was loaded flat or its length has changed since.
+    pub fn dimensions(&self, id: &Id) -> Option<Vec<usize>> {
+        let vals = self.data.get(id)?;
+        Some(
+            self.nested_dims(id)
+                .map_or_else(|| vec![vals.len()], <[usize]>::to_vec),
+        )
+    }
+
+    /// The shape the memory `id` was loaded with, if it was nested and still
+    /// holds the same number of values. The data can be resized through
+    /// [DerefMut], which leaves the recorded shape behind.
+    fn nested_dims(&self, id: &Id) -> Option<&[usize]> {
+        let dims = self.dims.get(id)?;
+        let len = self.data.get(id)?.len();
+        (dims.iter().product::<usize>() == len).then_some(dims.as_slice())
+    }
+
+    /// Writes this map to `path` in the JSON format read by
+    /// [MemoryMap::inflate_map]. Each memory records the width of its
+    /// elements so that loading the file again gives back the same values,
+    /// and errors with [InterpreterError::MixedMemoryWidths] if its elements
+    /// differ in width. Memories loaded from nested arrays are written nested
+    /// the same way, unless their length has changed since.
+    pub fn dump_to_json(&self, path: &Path) -> InterpreterResult<()> {
+        let entries = self
+            .iter()
+            .map(|(name, data)| {
+                let width = data.first().map(Value::width).unwrap_or_default();
+                if let Some(index) =
+                    data.iter().position(|v| v.width() != width)
+                {
+                    return Err(InterpreterError::MixedMemoryWidths {
+                        memory: *name,
+                        index,
+                        expected: width,
+                        given: data[index].width(),
+                    }
+                    .into());
+                }
+                let dims = self.nested_dims(name).unwrap_or_default();
+                let data = nest_values(data, dims);
+                Ok((name.to_string(), SizedMemoryEntry { width, data }))
+            })
+            .collect::<InterpreterResult<BTreeMap<_, _>>>()?;
+
+        let file = fs::File::create(path)?;
+        serde_json::to_writer_pretty(file, &entries)
+            .map_err(std::io::Error::from)?;
+        Ok(())
+    }
+}
+
+/// Describes one memory stored in a binary memory file. See
+/// [MemoryMap::inflate_binary].
+#[derive(Debug, Clone)]
+pub struct MemoryLayout {
+    /// Name of the memory cell
+    pub name: Id,
+    /// Bit width of each element
+    pub width: u64,
+    /// Number of elements in the memory
+    pub count: usize,
+}
+
+impl MemoryLayout {
+    /// The number of bytes a single element occupies in the file
+    fn element_bytes(&self) -> usize {
+        (self.width as usize + 7) / 8
+    }
+}
+
+impl MemoryMap {
+    /// Loads memories from a flat binary file. The file holds the memories
+    /// back to back in the order given by `layout`, with each element stored
+    /// little-endian in the fewest whole bytes that fit its width. Errors if
+    /// the file length does not match the layout exactly or if an element
+    /// sets bits beyond its width.
+    pub fn inflate_binary(
+        path: &Path,
+        layout: &[MemoryLayout],
+    ) -> InterpreterResult<Self> {
+        let bytes = fs::read(path)?;
+        let expected: usize =
+            layout.iter().map(|m| m.element_bytes() * m.count).sum();
+        if bytes.len() != expected {
+            return Err(InterpreterError::BinaryMemoryLength {
+                path: path.to_path_buf(),
+                expected,
+                actual: bytes.len(),
+            }
+            .into());
+        }
+
+        let mut map = HashMap::new();
+        let mut rest = bytes.as_slice();
+        for mem in layout {
+            let (data, tail) = rest.split_at(mem.element_bytes() * mem.count);
+            rest = tail;
+
+            let vals = if mem.element_bytes() == 0 {
+                vec![Value::zeroes(0_usize); mem.count]
+            } else {
+                data.chunks(mem.element_bytes())
+                    .map(|chunk| Value::from_bytes_le(mem.width, chunk))
+                    .collect::<InterpreterResult<_>>()?
+            };
+            map.insert(mem.name, vals);
+        }
+
+        Ok(map.into())
+    }
+}
+
+/// The number of entries held by a register or memory primitive, or `None` if
+/// the cell is not one of these
+fn memory_size(cell: &Cell) -> Option<u64> {
+    let dims = match cell.primitive_name()?.as_ref() {
+        "std_reg" => return Some(1),
+        "std_mem_d1" | "seq_mem_d1" => return cell.get_parameter("SIZE"),
+        "std_mem_d2" | "seq_mem_d2" => 2,
+        "std_mem_d3" | "seq_mem_d3" => 3,
+        "std_mem_d4" | "seq_mem_d4" => 4,
+        _ => return None,
+    };
+    (0..dims)
+        .map(|d| cell.get_parameter(format!("D{d}_SIZE").as_str()))
+        .product()
+}
+
+impl From<HashMap<Id, Vec<Value>>> for MemoryMap {
+    fn from(data: HashMap<Id, Vec<Value>>) -> Self {
+        Self {
+            data,
+            dims: HashMap::new(),
+        }
+    }
+}
+
+/// A single mismatched entry between two [MemoryMap]s.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MemoryDiff {
+    /// Name of the memory cell
+    pub cell: Id,
+    /// Index of the mismatched entry
+    pub index: usize,
+    /// The expected value, or None if the expected memory has no such entry
+    pub expected: Option<Value>,
+    /// The actual value, or None if the actual memory has no such entry
+    pub actual: Option<Value>,
+}
+
+impl std::fmt::Display for MemoryDiff {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let fmt_val = |v: &Option<Value>| match v {
+            Some(v) => format!("{} ({} bits)", v.as_unsigned(), v.width()),
+            None => "nothing".to_string(),
+        };
+        write!(
+            f,
+            "{}[{}]: expected {} but found {}",
+            self.cell,
+            self.index,
+            fmt_val(&self.expected),
+            fmt_val(&self.actual)
+        )
+    }
+}
+
+impl MemoryMap {
+    /// Compares this map, taken as the expected values, against `actual` and
+    /// returns every entry which differs. Values of different widths never
+    /// compare equal. Entries present on only one side are reported with
+    /// `None` for the missing side. The result is ordered by memory name and
+    /// then by index.
+    pub fn diff(&self, actual: &MemoryMap) -> Vec<MemoryDiff> {
+        let mut names: Vec<&Id> = self
+            .keys()
+            .chain(actual.keys().filter(|k| !self.contains_key(*k)))
+            .collect();
+        names.sort_by_key(|name| name.to_string());
+
+        let empty = vec![];
+        let mut diffs = vec![];
+
+        for name in names {
+            let expected = self.get(name).unwrap_or(&empty);
+            let found = actual.get(name).unwrap_or(&empty);
+
+            for index in 0..expected.len().max(found.len()) {
+                let (e, a) = (expected.get(index), found.get(index));
+                if e != a {
+                    diffs.push(MemoryDiff {
+                        cell: *name,
+                        index,
+                        expected: e.cloned(),
+                        actual: a.cloned(),
+                    })
+                }
+            }
+        }
+
+        diffs
+    }
+
+    /// Lists the elements which changed between this snapshot and a later
+    /// one, `newer`, as `(memory, index, old, new)` in the order given by
+    /// [MemoryMap::diff]. Only elements present in both snapshots are
+    /// included, so memories or entries which appear on one side alone are
+    /// skipped; use [MemoryMap::diff] to see those.
+    pub fn changes(&self, newer: &MemoryMap) -> Vec<(Id, usize, Value, Value)> {
+        self.diff(newer)
+            .into_iter()
+            .filter_map(|diff| {
+                Some((diff.cell, diff.index, diff.expected?, diff.actual?))
+            })
+            .collect()
+    }
 }
 
 impl Deref for MemoryMap {
     type Target = HashMap<Id, Vec<Value>>;
 
     fn deref(&self) -> &Self::Target {
-        &self.0
+        &self.data
     }
 }
 
 impl DerefMut for MemoryMap {
     fn deref_mut(&mut self) -> &mut Self::Target {
-        &mut self.0
+        &mut self.data
     }
 }
 
+/// Values to drive onto the input ports of the entry component over time,
+/// keyed by cycle and then by port. Ports are named by the component and the
+/// signature port, e.g. `main.in`. A value persists on its port until a later
+/// cycle overrides it.
+#[derive(Debug, Default, Clone)]
+pub struct StimulusMap(BTreeMap<u64, HashMap<Canonical, Value>>);
+
+impl StimulusMap {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Drive `value` onto `port` at the start of `cycle`
+    pub fn insert(&mut self, cycle: u64, port: Canonical, value: Value) {
+        self.0.entry(cycle).or_default().insert(port, value);
+    }
+
+    /// The values to apply at the start of `cycle`, if any
+    pub fn get(&self, cycle: u64) -> Option<&HashMap<Canonical, Value>> {
+        self.0.get(&cycle)
+    }
+
+    /// Checks that every stimulus names an input port of `comp` and matches
+    /// its width
+    pub fn validate(&self, comp: &Component) -> InterpreterResult<()> {
+        for (port, value) in self.0.values().flatten() {
+            let target = find_stimulus_port(comp, port)?;
+            let width = target.read().width;
+            if value.width() != width {
+                return Err(InterpreterError::StimulusWidthMismatch {
+                    port: port.to_string(),
+                    expected: width,
+                    given: value.width(),
+                }
+                .into());
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Finds the signature input port of `comp` named by `port`
+pub(crate) fn find_stimulus_port(
+    comp: &Component,
+    port: &Canonical,
+) -> InterpreterResult<ArcTex<Port>> {
+    let Canonical(comp_name, port_name) = port;
+    let sig = comp.signature.read();
+    // the signature ports are inverted so component inputs are outputs here
+    sig.ports
+        .iter()
+        .find(|p| {
+            let p = p.read();
+            p.name == *port_name && p.direction == Direction::Output
+        })
+        .filter(|_| *comp_name == comp.name)
+        .cloned()
+        .ok_or_else(|| {
+            InterpreterError::UnknownStimulusPort(port.to_string()).into()
+        })
+}
+
 /// Construct memory bindings.
 pub fn construct_bindings<const N: usize>(binds: [(&str, u64); N]) -> Binding {
     let mut vec = Binding::new();
@@ -91,6 +620,22 @@ pub fn construct_bindings<const N: usize>(binds: [(&str, u64); N]) -> Binding {
     vec
 }
 
+/// Construct bindings, rejecting any parameter name which appears more than
+/// once.
+pub fn construct_bindings_checked<const N: usize>(
+    binds: [(&str, u64); N],
+) -> InterpreterResult<Binding> {
+    let mut vec = Binding::new();
+    for (name, val) in binds {
+        let name = Id::from(name);
+        if vec.iter().any(|(existing, _)| *existing == name) {
+            return Err(InterpreterError::DuplicateParameter(name).into());
+        }
+        vec.push((name, val))
+    }
+    Ok(vec)
+}
+
 pub trait AsRaw<Target> {
     fn as_raw(&self) -> *const Target;
 }
@@ -136,6 +681,8 @@ impl<T> AsRaw<T> for &RRC<T> {
     }
 }
 
+/// Prints a Calyx IR assignment. Assignments in the interpreter IR implement
+/// [Display](std::fmt::Display) directly.
 #[allow(dead_code)]
 pub fn assignment_to_string(
     assignment: &calyx_ir::Assignment<Nothing>,
@@ -218,6 +765,11 @@ impl<T> WeakArcTex<T> {
         // fail gracelessly
         self.0.upgrade().unwrap()
     }
+
+    /// Upgrade the pointer, returning `None` if the target has been dropped.
+    pub fn try_upgrade(&self) -> Option<ArcTex<T>> {
+        self.0.upgrade()
+    }
 }
 
 impl<T> From<&ArcTex<T>> for WeakArcTex<T> {
@@ -232,6 +784,49 @@ impl<T> From<ArcTex<T>> for WeakArcTex<T> {
     }
 }
 
+/// A wrapper around a [Port] handle which hashes and compares by the identity
+/// of the port rather than its contents. Two `PortRef`s are equal exactly when
+/// they point to the same allocation.
+#[derive(Debug, Clone)]
+pub struct PortRef(ArcTex<Port>);
+
+impl PortRef {
+    pub fn new(port: &ArcTex<Port>) -> Self {
+        Self(Arc::clone(port))
+    }
+
+    /// Get the underlying port handle.
+    pub fn port(&self) -> &ArcTex<Port> {
+        &self.0
+    }
+}
+
+impl From<ArcTex<Port>> for PortRef {
+    fn from(port: ArcTex<Port>) -> Self {
+        Self(port)
+    }
+}
+
+impl AsRaw<Port> for PortRef {
+    fn as_raw(&self) -> *const Port {
+        self.0.data_ptr()
+    }
+}
+
+impl Hash for PortRef {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.0.data_ptr().hash(state);
+    }
+}
+
+impl PartialEq for PortRef {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+impl Eq for PortRef {}
+
 pub enum ArcTexOrConst<T> {
     Arc(ArcTex<T>),
     Const(*const T),